@@ -1,13 +1,17 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, path::Path};
 
 use testcontainers::{
-    core::{ContainerPort, WaitFor},
+    core::{ContainerPort, Mount, WaitFor},
     Image,
 };
 
+use crate::ModuleConfigError;
+
 const NAME: &str = "ghcr.io/foundry-rs/foundry";
 const TAG: &str = "stable@sha256:daeeaaf4383ee0cbfc9f31f079a04ffb0123e49e5f67f2a20b5ce1ac1959a4d6";
 const PORT: ContainerPort = ContainerPort::Tcp(8545);
+/// Directory inside of the container where [`AnvilNode::with_state_mount`] mounts the host directory
+pub const ANVIL_STATE_DIR: &str = "/state";
 
 /// # Community Testcontainers Implementation for [Foundry Anvil](https://book.getfoundry.sh/anvil/)
 ///
@@ -34,6 +38,17 @@ pub struct AnvilNode {
     chain_id: Option<u64>,
     fork_url: Option<String>,
     fork_block_number: Option<u64>,
+    fork_retries: Option<u32>,
+    fork_timeout: Option<u64>,
+    accounts: Option<u32>,
+    balance: Option<u64>,
+    mnemonic: Option<String>,
+    block_time: Option<u64>,
+    auto_impersonate: bool,
+    disable_block_gas_limit: bool,
+    config_out: Option<String>,
+    state_mount: Option<Mount>,
+    port: Option<ContainerPort>,
     tag: Option<String>,
 }
 
@@ -63,6 +78,107 @@ impl AnvilNode {
         self.fork_block_number = Some(block_number);
         self
     }
+
+    /// Specify how often requests to the fork URL are retried (`--retries`)
+    ///
+    /// Only applies when forking, see [`AnvilNode::with_fork_url`].
+    pub fn with_fork_retries(mut self, retries: u32) -> Self {
+        self.fork_retries = Some(retries);
+        self
+    }
+
+    /// Specify the timeout of requests to the fork URL in milliseconds (`--timeout`)
+    ///
+    /// Only applies when forking, see [`AnvilNode::with_fork_url`].
+    pub fn with_fork_timeout(mut self, timeout_ms: u64) -> Self {
+        self.fork_timeout = Some(timeout_ms);
+        self
+    }
+
+    /// Specify the number of dev accounts to generate and configure (`--accounts`), 10 by default
+    pub fn with_accounts(mut self, accounts: u32) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    /// Specify the balance of every dev account in Ether (`--balance`), 10000 by default
+    pub fn with_balance(mut self, balance: u64) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Specify the BIP39 mnemonic phrase used to derive the dev accounts (`--mnemonic`)
+    pub fn with_mnemonic(mut self, mnemonic: impl Into<String>) -> Self {
+        self.mnemonic = Some(mnemonic.into());
+        self
+    }
+
+    /// Mine a new block every `seconds` (`--block-time`)
+    ///
+    /// This disables automining: transactions are no longer mined as soon as they are received,
+    /// but only with the next block, so tests have to wait for it before checking receipts.
+    pub fn with_block_time(mut self, seconds: u64) -> Self {
+        self.block_time = Some(seconds);
+        self
+    }
+
+    /// Enable auto impersonation of accounts, so transactions can be sent from any address
+    /// without a signature. Useful when testing against a forked chain.
+    pub fn with_auto_impersonate(mut self) -> Self {
+        self.auto_impersonate = true;
+        self
+    }
+
+    /// Disable the block gas limit, so transactions are not rejected for exceeding it
+    pub fn with_disabled_block_gas_limit(mut self) -> Self {
+        self.disable_block_gas_limit = true;
+        self
+    }
+
+    /// Write the generated accounts and private keys as JSON to `path_in_container` (`--config-out`)
+    ///
+    /// Combine with [`AnvilNode::with_state_mount`] and a path inside of [`ANVIL_STATE_DIR`]
+    /// to read the file from the host.
+    pub fn with_config_out(mut self, path_in_container: impl Into<String>) -> Self {
+        self.config_out = Some(path_in_container.into());
+        self
+    }
+
+    /// Listen on `port` inside of the container (`--port`) instead of the default 8545
+    ///
+    /// The port is exposed instead of the default one, so use it to look up the mapped host port.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(ContainerPort::Tcp(port));
+        self
+    }
+
+    /// Returns the port Anvil listens on inside of the container
+    fn port(&self) -> ContainerPort {
+        self.port.unwrap_or(PORT)
+    }
+
+    /// Bind-mount `host_dir` to [`ANVIL_STATE_DIR`] inside of the container
+    ///
+    /// The directory has to be writable by the user of the Foundry image.
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [`AnvilNode::try_with_state_mount`].
+    pub fn with_state_mount(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_state_mount(host_dir)
+            .expect("state directory is not valid UTF-8")
+    }
+
+    /// Same as [`AnvilNode::with_state_mount`], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_state_mount(
+        mut self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        self.state_mount = Some(Mount::bind_mount(host_dir, ANVIL_STATE_DIR));
+        Ok(self)
+    }
 }
 
 impl Image for AnvilNode {
@@ -84,6 +200,56 @@ impl Image for AnvilNode {
             cmd.push(fork_block_number.to_string());
         }
 
+        if self.fork_url.is_some() {
+            if let Some(fork_retries) = self.fork_retries {
+                cmd.push("--retries".to_string());
+                cmd.push(fork_retries.to_string());
+            }
+
+            if let Some(fork_timeout) = self.fork_timeout {
+                cmd.push("--timeout".to_string());
+                cmd.push(fork_timeout.to_string());
+            }
+        }
+
+        if let Some(accounts) = self.accounts {
+            cmd.push("--accounts".to_string());
+            cmd.push(accounts.to_string());
+        }
+
+        if let Some(balance) = self.balance {
+            cmd.push("--balance".to_string());
+            cmd.push(balance.to_string());
+        }
+
+        if let Some(ref mnemonic) = self.mnemonic {
+            cmd.push("--mnemonic".to_string());
+            cmd.push(mnemonic.to_string());
+        }
+
+        if let Some(block_time) = self.block_time {
+            cmd.push("--block-time".to_string());
+            cmd.push(block_time.to_string());
+        }
+
+        if self.auto_impersonate {
+            cmd.push("--auto-impersonate".to_string());
+        }
+
+        if self.disable_block_gas_limit {
+            cmd.push("--disable-block-gas-limit".to_string());
+        }
+
+        if let Some(ref config_out) = self.config_out {
+            cmd.push("--config-out".to_string());
+            cmd.push(config_out.to_string());
+        }
+
+        if let Some(port) = self.port {
+            cmd.push("--port".to_string());
+            cmd.push(port.as_u16().to_string());
+        }
+
         cmd.into_iter().map(Cow::from)
     }
 
@@ -98,7 +264,14 @@ impl Image for AnvilNode {
     }
 
     fn expose_ports(&self) -> &[ContainerPort] {
-        &[PORT]
+        match &self.port {
+            Some(port) => std::slice::from_ref(port),
+            None => &[PORT],
+        }
+    }
+
+    fn mounts(&self) -> impl IntoIterator<Item = &Mount> {
+        self.state_mount.iter()
     }
 
     fn name(&self) -> &str {
@@ -110,7 +283,10 @@ impl Image for AnvilNode {
     }
 
     fn ready_conditions(&self) -> Vec<WaitFor> {
-        vec![WaitFor::message_on_stdout("Listening on 0.0.0.0:8545")]
+        vec![WaitFor::message_on_stdout(format!(
+            "Listening on 0.0.0.0:{}",
+            self.port().as_u16()
+        ))]
     }
 }
 
@@ -119,10 +295,24 @@ mod tests {
     use alloy_network::AnyNetwork;
     use alloy_provider::{Provider, RootProvider};
     use alloy_transport_http::Http;
-    use testcontainers::runners::AsyncRunner;
+    use serde_json::{json, Value};
+    use testcontainers::{core::ExecCommand, runners::AsyncRunner, ImageExt};
 
     use super::*;
 
+    #[cfg(unix)]
+    #[test]
+    fn anvil_state_mount_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/anvil-\xff"));
+        let result = AnvilNode::default().try_with_state_mount(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
     #[tokio::test]
     async fn test_anvil_node_container() {
         let _ = pretty_env_logger::try_init();
@@ -155,6 +345,239 @@ mod tests {
             vec!["--chain-id", "1337", "--fork-url", "http://example.com"]
         );
 
+        let mnemonic = "test test test test test test test test test test test junk";
+        let node = AnvilNode::default()
+            .with_block_time(2)
+            .with_mnemonic(mnemonic)
+            .with_balance(100)
+            .with_accounts(3)
+            .with_chain_id(1337);
+
+        let cmd: Vec<String> = node
+            .cmd()
+            .into_iter()
+            .map(|c| c.into().into_owned())
+            .collect();
+
+        assert_eq!(
+            cmd,
+            vec![
+                "--chain-id",
+                "1337",
+                "--accounts",
+                "3",
+                "--balance",
+                "100",
+                "--mnemonic",
+                mnemonic,
+                "--block-time",
+                "2"
+            ]
+        );
+
         assert_eq!(node.entrypoint(), Some("anvil"));
     }
+
+    #[test]
+    fn test_fork_retries_and_timeout_only_when_forking() {
+        let node = AnvilNode::default()
+            .with_fork_retries(3)
+            .with_fork_timeout(1500);
+
+        let cmd: Vec<String> = node
+            .cmd()
+            .into_iter()
+            .map(|c| c.into().into_owned())
+            .collect();
+        assert!(cmd.is_empty());
+
+        let node = node.with_fork_url("http://example.com");
+        let cmd: Vec<String> = node
+            .cmd()
+            .into_iter()
+            .map(|c| c.into().into_owned())
+            .collect();
+        assert_eq!(
+            cmd,
+            vec![
+                "--fork-url",
+                "http://example.com",
+                "--retries",
+                "3",
+                "--timeout",
+                "1500"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_anvil_fork_with_timeout() {
+        let _ = pretty_env_logger::try_init();
+
+        let network = format!("anvil-fork-{}", std::process::id());
+        let upstream_name = format!("anvil-upstream-{}", std::process::id());
+        let _upstream = AnvilNode::default()
+            .with_chain_id(1337)
+            .with_network(&network)
+            .with_container_name(&upstream_name)
+            .start()
+            .await
+            .unwrap();
+
+        let node = AnvilNode::default()
+            .with_fork_url(format!("http://{upstream_name}:8545"))
+            .with_fork_retries(2)
+            .with_fork_timeout(2000)
+            .with_network(&network)
+            .start()
+            .await
+            .unwrap();
+        let port = node.get_host_port_ipv4(PORT).await.unwrap();
+
+        let provider: RootProvider<Http<_>, AnyNetwork> =
+            RootProvider::new_http(format!("http://localhost:{port}").parse().unwrap());
+
+        // the fork inherits the chain id of the upstream
+        assert_eq!(provider.get_chain_id().await.unwrap(), 1337);
+    }
+
+    #[test]
+    fn test_custom_port() {
+        let node = AnvilNode::default().with_port(9545);
+
+        let cmd: Vec<String> = node
+            .cmd()
+            .into_iter()
+            .map(|c| c.into().into_owned())
+            .collect();
+
+        assert_eq!(cmd, vec!["--port", "9545"]);
+        assert_eq!(node.expose_ports(), &[ContainerPort::Tcp(9545)]);
+        assert_eq!(AnvilNode::default().expose_ports(), &[PORT]);
+    }
+
+    #[tokio::test]
+    async fn test_anvil_custom_port() {
+        let _ = pretty_env_logger::try_init();
+
+        let node = AnvilNode::default().with_port(9545).start().await.unwrap();
+        let port = node
+            .get_host_port_ipv4(ContainerPort::Tcp(9545))
+            .await
+            .unwrap();
+
+        let provider: RootProvider<Http<_>, AnyNetwork> =
+            RootProvider::new_http(format!("http://localhost:{port}").parse().unwrap());
+
+        assert_eq!(provider.get_block_number().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_impersonation_and_gas_limit_flags() {
+        let node = AnvilNode::default()
+            .with_auto_impersonate()
+            .with_disabled_block_gas_limit();
+
+        let cmd: Vec<String> = node
+            .cmd()
+            .into_iter()
+            .map(|c| c.into().into_owned())
+            .collect();
+
+        assert_eq!(cmd, vec!["--auto-impersonate", "--disable-block-gas-limit"]);
+    }
+
+    #[tokio::test]
+    async fn test_anvil_auto_impersonate() {
+        let _ = pretty_env_logger::try_init();
+
+        let node = AnvilNode::default()
+            .with_auto_impersonate()
+            .start()
+            .await
+            .unwrap();
+        let port = node.get_host_port_ipv4(PORT).await.unwrap();
+
+        let provider: RootProvider<Http<_>, AnyNetwork> =
+            RootProvider::new_http(format!("http://localhost:{port}").parse().unwrap());
+
+        let sender = "0x000000000000000000000000000000000000dEaD";
+        let _: Value = provider
+            .raw_request("anvil_setBalance".into(), (sender, "0x56BC75E2D63100000"))
+            .await
+            .unwrap();
+
+        let tx_hash: String = provider
+            .raw_request(
+                "eth_sendTransaction".into(),
+                (json!({
+                    "from": sender,
+                    "to": "0x0000000000000000000000000000000000000001",
+                    "value": "0x1",
+                }),),
+            )
+            .await
+            .unwrap();
+
+        let receipt: Value = provider
+            .raw_request("eth_getTransactionReceipt".into(), (tx_hash,))
+            .await
+            .unwrap();
+
+        assert_eq!(receipt["status"], "0x1");
+        assert_eq!(receipt["from"], sender.to_lowercase());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_anvil_config_out() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _ = pretty_env_logger::try_init();
+
+        let state_dir = std::env::temp_dir().join(format!("anvil-state-{}", std::process::id()));
+        std::fs::create_dir_all(&state_dir).unwrap();
+        // the Foundry image doesn't run as root
+        std::fs::set_permissions(&state_dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+        let node = AnvilNode::default()
+            .with_state_mount(&state_dir)
+            .with_config_out(format!("{ANVIL_STATE_DIR}/config.json"))
+            .start()
+            .await
+            .unwrap();
+
+        let config: Value =
+            serde_json::from_slice(&std::fs::read(state_dir.join("config.json")).unwrap()).unwrap();
+        let private_key = config["private_keys"][0].as_str().unwrap();
+
+        let mut send = node
+            .exec(ExecCommand::new([
+                "cast",
+                "send",
+                "--json",
+                "--rpc-url",
+                "http://localhost:8545",
+                "--private-key",
+                private_key,
+                "--value",
+                "1",
+                "0x0000000000000000000000000000000000000001",
+            ]))
+            .await
+            .unwrap();
+        let receipt: Value = serde_json::from_slice(&send.stdout_to_vec().await.unwrap()).unwrap();
+
+        assert_eq!(receipt["status"], "0x1");
+        assert_eq!(
+            receipt["from"].as_str().unwrap().to_lowercase(),
+            config["available_accounts"][0]
+                .as_str()
+                .unwrap()
+                .to_lowercase()
+        );
+
+        drop(node);
+        let _ = std::fs::remove_dir_all(state_dir);
+    }
 }
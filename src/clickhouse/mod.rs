@@ -1,10 +1,12 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{borrow::Cow, collections::BTreeMap, time::Duration};
 
 use testcontainers::{
-    core::{wait::HttpWaitStrategy, ContainerPort, WaitFor},
-    Image,
+    core::{wait::HttpWaitStrategy, ContainerPort, ContainerState, ExecCommand, WaitFor},
+    CopyDataSource, CopyToContainer, Image, TestcontainersError,
 };
 
+use crate::util::retry_until_success;
+
 const DEFAULT_IMAGE_NAME: &str = "clickhouse/clickhouse-server";
 const DEFAULT_IMAGE_TAG: &str = "23.3.8.21-alpine";
 
@@ -14,6 +16,37 @@ const DEFAULT_IMAGE_TAG: &str = "23.3.8.21-alpine";
 /// [`ClickHouse`]: https://clickhouse.com/
 pub const CLICKHOUSE_PORT: ContainerPort = ContainerPort::Tcp(8123);
 
+/// Config of the embedded ClickHouse Keeper and the server using it, see [`ClickHouse::with_keeper`].
+const KEEPER_CONFIG: &str = r#"<clickhouse>
+    <keeper_server>
+        <tcp_port>9181</tcp_port>
+        <server_id>1</server_id>
+        <log_storage_path>/var/lib/clickhouse/coordination/log</log_storage_path>
+        <snapshot_storage_path>/var/lib/clickhouse/coordination/snapshots</snapshot_storage_path>
+        <raft_configuration>
+            <server>
+                <id>1</id>
+                <hostname>localhost</hostname>
+                <port>9234</port>
+            </server>
+        </raft_configuration>
+    </keeper_server>
+    <zookeeper>
+        <node>
+            <host>localhost</host>
+            <port>9181</port>
+        </node>
+    </zookeeper>
+    <macros>
+        <shard>01</shard>
+        <replica>replica01</replica>
+    </macros>
+</clickhouse>
+"#;
+/// Query of the root node of the keeper, which succeeds once the server is connected to it.
+const KEEPER_PROBE: &str =
+    "clickhouse-client -q \"SELECT count() FROM system.zookeeper WHERE path = '/'\" >/dev/null 2>&1";
+
 /// Module to work with [`ClickHouse`] inside of tests.
 ///
 /// This module is based on the official [`ClickHouse docker image`].
@@ -33,6 +66,26 @@ pub const CLICKHOUSE_PORT: ContainerPort = ContainerPort::Tcp(8123);
 #[derive(Debug, Default, Clone)]
 pub struct ClickHouse {
     env_vars: BTreeMap<String, String>,
+    copy_to_sources: Vec<CopyToContainer>,
+    keeper: bool,
+}
+
+impl ClickHouse {
+    /// Enables the embedded [ClickHouse Keeper](https://clickhouse.com/docs/en/guides/sre/keeper/clickhouse-keeper),
+    /// so `Replicated*MergeTree` tables can be created on this single node.
+    ///
+    /// The `{shard}` and `{replica}` macros are defined as well, e.g.
+    /// `ENGINE = ReplicatedMergeTree('/clickhouse/tables/{shard}/t', '{replica}')`.
+    /// The container is ready once the server is able to query `system.zookeeper`,
+    /// i.e. it's connected to the keeper.
+    pub fn with_keeper(mut self) -> Self {
+        self.keeper = true;
+        self.copy_to_sources.push(CopyToContainer::new(
+            CopyDataSource::Data(KEEPER_CONFIG.as_bytes().to_vec()),
+            "/etc/clickhouse-server/config.d/keeper.xml",
+        ));
+        self
+    }
 }
 
 impl Image for ClickHouse {
@@ -56,9 +109,28 @@ impl Image for ClickHouse {
         &self.env_vars
     }
 
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        &self.copy_to_sources
+    }
+
     fn expose_ports(&self) -> &[ContainerPort] {
         &[CLICKHOUSE_PORT]
     }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        if !self.keeper {
+            return Ok(vec![]);
+        }
+        Ok(vec![retry_until_success(
+            KEEPER_PROBE,
+            Vec::<String>::new(),
+            300,
+            Duration::from_millis(200),
+        )])
+    }
 }
 
 #[cfg(test)]
@@ -110,4 +182,30 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn clickhouse_replicated_table_with_keeper(
+    ) -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = ClickhouseImage::default().with_keeper().start().await?;
+
+        let host = node.get_host().await?;
+        let port = node.get_host_port_ipv4(8123).await?;
+        let url = format!("http://{}:{}", host, port);
+
+        let query = "CREATE TABLE t (a UInt8) \
+                     ENGINE = ReplicatedMergeTree('/clickhouse/tables/{shard}/t', '{replica}') \
+                     ORDER BY a";
+        let response = Client::new().post(url.clone()).body(query).send().await?;
+        assert_eq!(response.status(), 200);
+
+        let query = "INSERT INTO t VALUES (1),(2),(3)";
+        let response = Client::new().post(url.clone()).body(query).send().await?;
+        assert_eq!(response.status(), 200);
+
+        let query = "SELECT a FROM t ORDER BY a FORMAT TabSeparated";
+        let response = Client::new().post(url.clone()).body(query).send().await?;
+        assert_eq!(response.text().await?, "1\n2\n3\n");
+
+        Ok(())
+    }
 }
@@ -1,6 +1,9 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt::Display};
 
-use testcontainers::{core::WaitFor, Image};
+use testcontainers::{
+    core::{CmdWaitFor, ContainerPort, ContainerState, ExecCommand, WaitFor},
+    Image, TestcontainersError,
+};
 
 const DEFAULT_IMAGE_NAME: &str = "cockroachdb/cockroach";
 const DEFAULT_IMAGE_TAG: &str = "v23.2.3";
@@ -25,12 +28,73 @@ const DEFAULT_IMAGE_TAG: &str = "v23.2.3";
 #[derive(Debug, Default, Clone)]
 pub struct CockroachDb {
     cmd: CockroachDbCmd,
+    locality: Option<String>,
+    workloads: Vec<String>,
+    http_addr: Option<String>,
+    http_port: Option<ContainerPort>,
 }
 
 impl CockroachDb {
     /// Create a new instance of a CockroachDb image.
     pub fn new(cmd: CockroachDbCmd) -> Self {
-        CockroachDb { cmd }
+        CockroachDb {
+            cmd,
+            locality: None,
+            workloads: Vec::new(),
+            http_addr: None,
+            http_port: None,
+        }
+    }
+
+    /// Sets the `--locality` of the node, e.g. `[("region", "us-east1"), ("zone", "us-east1-b")]`.
+    ///
+    /// The tiers are passed in the given order and describe the node from the most inclusive to
+    /// the least inclusive tier. This allows testing multi-region SQL features on a single node.
+    /// See the [`locality documentation`](https://www.cockroachlabs.com/docs/stable/cockroach-start#locality).
+    pub fn with_locality(mut self, kv: &[(String, String)]) -> Self {
+        let locality = kv
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.locality = Some(locality);
+        self
+    }
+
+    /// Sets the `--http-addr` the DB Console and the HTTP API listen on, e.g. `0.0.0.0:8090`,
+    /// instead of the default `:8080`.
+    ///
+    /// The port of the address is exposed, so the mapped host port can be looked up with it.
+    /// See the [`start documentation`](https://www.cockroachlabs.com/docs/stable/cockroach-start#networking).
+    pub fn with_http_addr(mut self, addr: impl Into<String>) -> Self {
+        let addr = addr.into();
+        self.http_port = addr
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse().ok())
+            .map(ContainerPort::Tcp);
+        self.http_addr = Some(addr);
+        self
+    }
+
+    /// Returns the URL of the readiness endpoint `/health?ready=1`, with `host_port` being the
+    /// host port the HTTP port of the node is mapped to. It responds with `200 OK` once the node
+    /// accepts SQL connections.
+    pub fn health_url(&self, host: impl Display, host_port: u16) -> String {
+        let scheme = match self.cmd {
+            CockroachDbCmd::StartSingleNode { insecure: true } => "http",
+            CockroachDbCmd::StartSingleNode { insecure: false } => "https",
+        };
+        format!("{scheme}://{host}:{host_port}/health?ready=1")
+    }
+
+    /// Runs `cockroach workload init <workload>` after start, so tests have a ready schema with data,
+    /// e.g. `bank` or `movr`. Can be called multiple times to initialize several workloads.
+    ///
+    /// Requires an `insecure` node. See the [`workload documentation`](https://www.cockroachlabs.com/docs/stable/cockroach-workload)
+    /// for the available workloads.
+    pub fn with_workload_init(mut self, workload: impl Into<String>) -> Self {
+        self.workloads.push(workload.into());
+        self
     }
 }
 
@@ -77,7 +141,38 @@ impl Image for CockroachDb {
     }
 
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
-        self.cmd
+        let mut cmd: Vec<String> = self.cmd.into_iter().collect();
+        if let Some(locality) = &self.locality {
+            cmd.push(format!("--locality={locality}"));
+        }
+        if let Some(http_addr) = &self.http_addr {
+            cmd.push(format!("--http-addr={http_addr}"));
+        }
+        cmd
+    }
+
+    fn expose_ports(&self) -> &[ContainerPort] {
+        self.http_port.as_slice()
+    }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        Ok(self
+            .workloads
+            .iter()
+            .map(|workload| {
+                ExecCommand::new([
+                    "/cockroach/cockroach",
+                    "workload",
+                    "init",
+                    workload,
+                    "postgresql://root@localhost:26257?sslmode=disable",
+                ])
+                .with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+            })
+            .collect())
     }
 }
 
@@ -124,4 +219,61 @@ mod tests {
         assert_eq!(first_column, 2);
         Ok(())
     }
+
+    #[test]
+    fn cockroach_db_with_locality() {
+        let cockroach = CockroachDb::default().with_locality(&[
+            ("region".to_string(), "us-east1".to_string()),
+            ("zone".to_string(), "us-east1-b".to_string()),
+        ]);
+
+        let cmd: Vec<String> = cockroach
+            .cmd()
+            .into_iter()
+            .map(|c| c.into().into_owned())
+            .collect();
+        assert_eq!(
+            cmd,
+            vec![
+                "start-single-node",
+                "--insecure",
+                "--locality=region=us-east1,zone=us-east1-b",
+            ]
+        );
+    }
+
+    #[test]
+    fn cockroach_db_with_workload_init() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = CockroachDb::default().with_workload_init("bank").start()?;
+
+        let connection_string = &format!(
+            "postgresql://root@127.0.0.1:{}/bank?sslmode=disable",
+            node.get_host_port_ipv4(26257.tcp())?
+        );
+        let mut conn = postgres::Client::connect(connection_string, postgres::NoTls).unwrap();
+
+        // the `bank` workload stores its accounts in the `bank.bank` table
+        let rows = conn.query("SELECT count(*) FROM bank.bank", &[]).unwrap();
+        let accounts: i64 = rows[0].get(0);
+        assert!(accounts > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn cockroach_db_health_url() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let cockroach = CockroachDb::default().with_http_addr("0.0.0.0:8090");
+        assert_eq!(cockroach.expose_ports(), [8090.tcp()]);
+        assert_eq!(
+            cockroach.health_url("127.0.0.1", 18090),
+            "http://127.0.0.1:18090/health?ready=1"
+        );
+
+        let node = cockroach.start()?;
+        let url = node
+            .image()
+            .health_url(node.get_host()?, node.get_host_port_ipv4(8090.tcp())?);
+        let response = reqwest::blocking::get(url)?;
+        assert_eq!(response.status(), 200);
+        Ok(())
+    }
 }
@@ -1,12 +1,19 @@
 use std::borrow::Cow;
 
 use testcontainers::{
-    core::{ContainerPort, WaitFor},
-    Image,
+    core::{
+        wait::HttpWaitStrategy, CmdWaitFor, ContainerPort, ContainerState, ExecCommand, WaitFor,
+    },
+    Image, TestcontainersError,
 };
 
+use crate::ResourceLimits;
+
 const NAME: &str = "docker.elastic.co/elasticsearch/elasticsearch";
 const TAG: &str = "7.16.1";
+/// Waits for the cluster to be at least yellow and creates index `$2` with settings `$3`
+/// using the API at `$1`.
+const CREATE_INDEX_SCRIPT: &str = r#"curl -sf "$1/_cluster/health?wait_for_status=yellow&timeout=50s" >/dev/null && curl -sf -X PUT "$1/$2" -H 'Content-Type: application/json' -d "$3""#;
 /// Port that the [`Elasticsearch`] container has internally
 /// Used **for API calls over http**, including search, aggregation, monitoring, ...
 /// Client libraries have switched to using this to communicate to elastic.
@@ -20,15 +27,87 @@ pub const ELASTICSEARCH_API_PORT: ContainerPort = ContainerPort::Tcp(9200);
 ///
 /// [`Elasticsearch`]: https://elastic.co/
 pub const ELASTICSEARCH_INTER_NODE_PORT: ContainerPort = ContainerPort::Tcp(9300);
+/// Smallest JVM heap size [`ElasticSearch::with_heap_size_bytes`] configures.
+///
+/// Elasticsearch fails to boot with a much smaller heap, so smaller values are raised to this one.
+pub const ELASTICSEARCH_MIN_HEAP_SIZE_BYTES: u64 = 256 * 1024 * 1024;
 
 #[allow(missing_docs)]
 // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
 #[derive(Debug, Default, Clone)]
 pub struct ElasticSearch {
-    /// (remove if there is another variable)
-    /// Field is included to prevent this struct to be a unit struct.
-    /// This allows extending functionality (and thus further variables) without breaking changes
-    _priv: (),
+    min_cluster_status: ClusterStatus,
+    heap_size_bytes: Option<u64>,
+    indices: Vec<Index>,
+}
+
+/// Index created after startup, see [`ElasticSearch::with_index`].
+#[derive(Debug, Clone)]
+struct Index {
+    name: String,
+    shards: u32,
+    replicas: u32,
+}
+
+/// Health status of the cluster, as reported by the [`cluster health API`].
+///
+/// [`cluster health API`]: https://www.elastic.co/guide/en/elasticsearch/reference/7.16/cluster-health.html
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterStatus {
+    /// All shards are assigned.
+    Green,
+    /// All primary shards are assigned, but one or more replica shards are unassigned.
+    /// This is the usual state of a single-node cluster.
+    #[default]
+    Yellow,
+    /// One or more primary shards are unassigned.
+    Red,
+}
+
+impl ClusterStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ClusterStatus::Green => "green",
+            ClusterStatus::Yellow => "yellow",
+            ClusterStatus::Red => "red",
+        }
+    }
+}
+
+impl ElasticSearch {
+    /// Sets the cluster health status to wait for before the container is considered ready.
+    ///
+    /// Defaults to [`ClusterStatus::Yellow`].
+    pub fn with_min_cluster_status(mut self, status: ClusterStatus) -> Self {
+        self.min_cluster_status = status;
+        self
+    }
+
+    /// Creates an index with the given number of primary `shards` and `replicas` once the
+    /// cluster health is at least yellow, so it exists as soon as the container is started.
+    ///
+    /// Can be called multiple times to create several indices.
+    /// Note that replicas are never assigned in a single-node cluster, so use `0` replicas
+    /// together with [`ClusterStatus::Green`].
+    pub fn with_index(mut self, name: impl Into<String>, shards: u32, replicas: u32) -> Self {
+        self.indices.push(Index {
+            name: name.into(),
+            shards,
+            replicas,
+        });
+        self
+    }
+}
+
+impl ResourceLimits for ElasticSearch {
+    /// Sets the minimum and maximum JVM heap size of Elasticsearch via `ES_JAVA_OPTS`.
+    ///
+    /// The value is rounded down to whole mebibytes, values below
+    /// [`ELASTICSEARCH_MIN_HEAP_SIZE_BYTES`] are raised to it.
+    fn with_heap_size_bytes(mut self, bytes: u64) -> Self {
+        self.heap_size_bytes = Some(bytes);
+        self
+    }
 }
 
 impl Image for ElasticSearch {
@@ -41,19 +120,195 @@ impl Image for ElasticSearch {
     }
 
     fn ready_conditions(&self) -> Vec<WaitFor> {
-        vec![WaitFor::message_on_stdout("[YELLOW] to [GREEN]")]
+        vec![WaitFor::http(
+            HttpWaitStrategy::new(format!(
+                "/_cluster/health?wait_for_status={}&timeout=50s",
+                self.min_cluster_status.as_str()
+            ))
+            .with_port(ELASTICSEARCH_API_PORT)
+            .with_expected_status_code(200_u16),
+        )]
     }
 
     fn env_vars(
         &self,
     ) -> impl IntoIterator<Item = (impl Into<Cow<'_, str>>, impl Into<Cow<'_, str>>)> {
-        [("discovery.type", "single-node")]
+        let mut env_vars = vec![("discovery.type".to_owned(), "single-node".to_owned())];
+        if let Some(bytes) = self.heap_size_bytes {
+            let mebibytes = bytes.max(ELASTICSEARCH_MIN_HEAP_SIZE_BYTES) / (1024 * 1024);
+            env_vars.push((
+                "ES_JAVA_OPTS".to_owned(),
+                format!("-Xms{mebibytes}m -Xmx{mebibytes}m"),
+            ));
+        }
+        env_vars
     }
 
     fn expose_ports(&self) -> &[ContainerPort] {
         &[ELASTICSEARCH_API_PORT, ELASTICSEARCH_INTER_NODE_PORT]
     }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        let base_url = format!("http://localhost:{}", ELASTICSEARCH_API_PORT.as_u16());
+        let commands = self
+            .indices
+            .iter()
+            .map(|index| {
+                let settings = format!(
+                    r#"{{"settings":{{"number_of_shards":{},"number_of_replicas":{}}}}}"#,
+                    index.shards, index.replicas
+                );
+                ExecCommand::new([
+                    "sh".to_owned(),
+                    "-c".to_owned(),
+                    CREATE_INDEX_SCRIPT.to_owned(),
+                    "sh".to_owned(),
+                    base_url.clone(),
+                    index.name.clone(),
+                    settings,
+                ])
+                .with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+            })
+            .collect();
+        Ok(commands)
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use testcontainers::runners::AsyncRunner;
+
+    use super::*;
+
+    #[test]
+    fn heap_size_sets_java_opts() {
+        let java_opts = |image: ElasticSearch| {
+            image
+                .env_vars()
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .find(|(key, _): &(Cow<'_, str>, Cow<'_, str>)| key == "ES_JAVA_OPTS")
+                .map(|(_, value)| value.into_owned())
+        };
+
+        assert_eq!(java_opts(ElasticSearch::default()), None);
+        assert_eq!(
+            java_opts(ElasticSearch::default().with_heap_size_bytes(512 * 1024 * 1024 + 1)),
+            Some("-Xms512m -Xmx512m".to_owned())
+        );
+        assert_eq!(
+            java_opts(ElasticSearch::default().with_heap_size_bytes(1024)),
+            Some("-Xms256m -Xmx256m".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn elasticsearch_index_right_after_start(
+    ) -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = ElasticSearch::default().start().await?;
+        let host = node.get_host().await?;
+        let port = node.get_host_port_ipv4(ELASTICSEARCH_API_PORT).await?;
+
+        let response = reqwest::Client::new()
+            .put(format!("http://{host}:{port}/test-index/_doc/1"))
+            .header("Content-Type", "application/json")
+            .body(r#"{"title": "testcontainers"}"#)
+            .send()
+            .await?;
+        assert_eq!(response.status(), 201);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn elasticsearch_with_min_heap_size() -> Result<(), Box<dyn std::error::Error + 'static>>
+    {
+        let node = ElasticSearch::default()
+            .with_heap_size_bytes(ELASTICSEARCH_MIN_HEAP_SIZE_BYTES)
+            .with_nofile_limit(65536, Some(65536))
+            .start()
+            .await?;
+        let host = node.get_host().await?;
+        let port = node.get_host_port_ipv4(ELASTICSEARCH_API_PORT).await?;
+        let client = reqwest::Client::new();
+
+        let stats = client
+            .get(format!("http://{host}:{port}/_nodes/stats/jvm"))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let heap_max = stats["nodes"]
+            .as_object()
+            .and_then(|nodes| nodes.values().next())
+            .and_then(|node| node["jvm"]["mem"]["heap_max_in_bytes"].as_u64());
+        assert_eq!(heap_max, Some(ELASTICSEARCH_MIN_HEAP_SIZE_BYTES));
+
+        // the node doesn't only boot with the smallest heap, but also serves requests
+        let response = client
+            .put(format!(
+                "http://{host}:{port}/test-index/_doc/1?refresh=true"
+            ))
+            .header("Content-Type", "application/json")
+            .body(r#"{"title": "testcontainers"}"#)
+            .send()
+            .await?;
+        assert_eq!(response.status(), 201);
+        let document = client
+            .get(format!("http://{host}:{port}/test-index/_doc/1"))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(document["_source"]["title"], "testcontainers");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn elasticsearch_with_index() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = ElasticSearch::default()
+            .with_index("books", 2, 0)
+            .start()
+            .await?;
+        let host = node.get_host().await?;
+        let port = node.get_host_port_ipv4(ELASTICSEARCH_API_PORT).await?;
+        let client = reqwest::Client::new();
+
+        let settings = client
+            .get(format!("http://{host}:{port}/books/_settings"))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(
+            settings["books"]["settings"]["index"]["number_of_shards"],
+            "2"
+        );
+
+        let bulk = concat!(
+            "{\"index\":{}}\n{\"title\":\"first\"}\n",
+            "{\"index\":{}}\n{\"title\":\"second\"}\n",
+            "{\"index\":{}}\n{\"title\":\"third\"}\n",
+        );
+        let response = client
+            .post(format!("http://{host}:{port}/books/_bulk?refresh=true"))
+            .header("Content-Type", "application/x-ndjson")
+            .body(bulk)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(response["errors"], false);
+
+        let count = client
+            .get(format!("http://{host}:{port}/books/_count"))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(count["count"], 3);
+        Ok(())
+    }
+}
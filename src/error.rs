@@ -0,0 +1,36 @@
+use std::{fmt, path::PathBuf};
+
+/// Error returned by the fallible `try_with_*` builder methods of the modules,
+/// when the provided configuration can't be passed to the container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ModuleConfigError {
+    /// The path isn't valid UTF-8, so it can't be passed to Docker.
+    NonUtf8Path(PathBuf),
+}
+
+impl fmt::Display for ModuleConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonUtf8Path(path) => write!(f, "path is not valid UTF-8: {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ModuleConfigError {}
+
+impl ModuleConfigError {
+    /// Returns `path` as `&str`, or [`ModuleConfigError::NonUtf8Path`] if it isn't valid UTF-8.
+    #[cfg(any(
+        feature = "anvil",
+        feature = "gitea",
+        feature = "k3s",
+        feature = "kafka",
+        feature = "meilisearch",
+        feature = "neo4j"
+    ))]
+    pub(crate) fn utf8_path(path: &std::path::Path) -> Result<&str, Self> {
+        path.to_str()
+            .ok_or_else(|| Self::NonUtf8Path(path.to_path_buf()))
+    }
+}
@@ -1,12 +1,20 @@
 /// Self-hosted git server with https/http/ssh access, uses [Gitea](https://docs.gitea.com/).
-use std::result::Result;
+use std::{
+    borrow::Cow, collections::BTreeMap, fmt::Display, path::Path, result::Result, time::Duration,
+};
 
-use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
     core::{
-        wait::HttpWaitStrategy, CmdWaitFor, ContainerPort, ContainerState, ExecCommand, WaitFor,
+        wait::HttpWaitStrategy, CmdWaitFor, ContainerPort, ContainerState, ExecCommand, Mount,
+        WaitFor,
     },
-    CopyDataSource, CopyToContainer, Image, TestcontainersError,
+    ContainerAsync, CopyDataSource, CopyToContainer, Image, TestcontainersError,
+};
+
+use crate::{
+    tls::GeneratedCert, util::retry_until_success, HealthProbe, ImageReference, ModuleConfigError,
 };
 
 /// Container port for SSH listener.
@@ -25,6 +33,10 @@ pub const GITEA_DEFAULT_ADMIN_PASSWORD: &str = "git-admin";
 pub const GITEA_CONFIG_FOLDER: &str = "/etc/gitea";
 /// Container folder with git data: repos, DB, etc.
 pub const GITEA_DATA_FOLDER: &str = "/var/lib/gitea";
+/// Container folder the SQLite database is moved to by [Gitea::with_sqlite_mount()].
+pub const GITEA_SQLITE_FOLDER: &str = "/var/lib/gitea/db";
+/// File name of the SQLite database.
+pub const GITEA_SQLITE_FILE_NAME: &str = "gitea.db";
 
 /// Docker hub registry with gitea image.
 const GITEA_IMAGE_NAME: &str = "gitea/gitea";
@@ -37,6 +49,40 @@ const TLS_CERT_FILE_NAME: &str = "cert.pem";
 const TLS_KEY_FILE_NAME: &str = "key.pem";
 /// File name with a Gitea config.
 const CONFIG_FILE_NAME: &str = "app.ini";
+/// Line of the app.ini template with the default instance name.
+const DEFAULT_APP_NAME_LINE: &str = "APP_NAME = Gitea: Git with a cup of tea";
+/// Request to the branches API of a repository (`$2`) with credentials `$1`,
+/// which succeeds once it lists a branch, used by [`Gitea::with_repo_ready_check`].
+const BRANCHES_PROBE: &str = r#"curl -sk -u "$1" "$2" | grep -q '"name"'"#;
+/// Cron tasks Gitea enables by default, each disabled in its own `[cron.<task>]` section
+/// by [Gitea::with_cron_disabled()], as the `ENABLED` key of `[cron]` doesn't apply to them.
+const DEFAULT_CRON_TASKS: &[&str] = &[
+    "update_mirrors",
+    "repo_health_check",
+    "check_repo_stats",
+    "archive_cleanup",
+    "sync_external_users",
+    "deleted_branches_cleanup",
+    "update_migration_poster_id",
+    "cleanup_hook_task_table",
+    "cleanup_packages",
+    "cleanup_actions",
+    "stop_zombie_tasks",
+    "stop_endless_tasks",
+    "cancel_abandoned_jobs",
+];
+/// Keys of the `[server]` section rendered into app.ini by the module,
+/// which can't be overridden by environment variables.
+const RENDERED_SERVER_KEYS: &[&str] = &[
+    "DOMAIN",
+    "SSH_DOMAIN",
+    "ROOT_URL",
+    "PROTOCOL",
+    "CERT_FILE",
+    "KEY_FILE",
+    "REDIRECT_OTHER_PORT",
+    "PORT_TO_REDIRECT",
+];
 
 /// Module to work with [Gitea](https://docs.gitea.com/) container.
 ///
@@ -63,7 +109,9 @@ const CONFIG_FILE_NAME: &str = "app.ini";
 /// - provide your own admin user credentials as well as its SSH public key to authorize git calls;
 /// - create any number of public or private repositories with provided names during server startup;
 /// - execute set of `gitea admin ...` commands during server startup to customize configuration;
-/// - add environment variables
+/// - add environment variables with [`Gitea::with_env_var`], e.g. to tune settings which have no dedicated method;
+/// - set a fixed container name with [`ImageExt::with_container_name`](testcontainers::ImageExt::with_container_name),
+///   e.g. to correlate logs or to address the server from other containers on the same network
 ///
 /// # Examples
 ///
@@ -142,14 +190,23 @@ const CONFIG_FILE_NAME: &str = "app.ini";
 /// ```
 #[derive(Debug, Clone)]
 pub struct Gitea {
+    tag: Option<String>,
+    app_name: Option<String>,
     git_hostname: String,
     admin_username: String,
     admin_password: String,
     admin_key: Option<String>,
     admin_commands: Vec<Vec<String>>,
     tls: Option<GiteaTlsCert>,
+    http_redirect: bool,
     repos: Vec<GiteaRepo>,
+    repo_ready_check: bool,
+    default_branch: Option<String>,
+    migrated_repos: Vec<GiteaMigratedRepo>,
+    app_ini_config: BTreeMap<String, BTreeMap<String, String>>,
+    env_vars: BTreeMap<String, String>,
     copy_to_sources: Vec<CopyToContainer>,
+    sqlite_mount: Option<Mount>,
 }
 
 impl Default for Gitea {
@@ -162,15 +219,28 @@ impl Default for Gitea {
     /// - without TLS (SSH and HTTP protocols only);
     /// - without repositories.
     fn default() -> Self {
-        Self {
+        let gitea = Self {
+            tag: None,
+            app_name: None,
             git_hostname: "localhost".to_string(),
             admin_username: GITEA_DEFAULT_ADMIN_USERNAME.to_string(),
             admin_password: GITEA_DEFAULT_ADMIN_PASSWORD.to_string(),
             admin_key: None,
             admin_commands: vec![],
             tls: None,
+            http_redirect: true,
             repos: vec![],
-            copy_to_sources: vec![Self::render_app_ini("http", "localhost", false)],
+            repo_ready_check: false,
+            default_branch: None,
+            migrated_repos: vec![],
+            app_ini_config: BTreeMap::new(),
+            env_vars: BTreeMap::new(),
+            copy_to_sources: vec![],
+            sqlite_mount: None,
+        };
+        Self {
+            copy_to_sources: gitea.generate_copy_to_sources(),
+            ..gitea
         }
     }
 }
@@ -181,7 +251,7 @@ impl Image for Gitea {
     }
 
     fn tag(&self) -> &str {
-        GITEA_IMAGE_TAG
+        self.tag.as_deref().unwrap_or(GITEA_IMAGE_TAG)
     }
 
     fn ready_conditions(&self) -> Vec<WaitFor> {
@@ -203,12 +273,24 @@ impl Image for Gitea {
         ]
     }
 
+    fn env_vars(
+        &self,
+    ) -> impl IntoIterator<Item = (impl Into<Cow<'_, str>>, impl Into<Cow<'_, str>>)> {
+        self.env_vars
+            .iter()
+            .filter(|(key, _)| !is_rendered_server_key(key))
+    }
+
     fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
         &self.copy_to_sources
     }
 
+    fn mounts(&self) -> impl IntoIterator<Item = &Mount> {
+        self.sqlite_mount.as_ref()
+    }
+
     fn expose_ports(&self) -> &[ContainerPort] {
-        if self.tls.is_some() {
+        if self.redirects_http() {
             // additional port for HTTP with redirect to HTTPS
             &[GITEA_SSH_PORT, GITEA_HTTP_PORT, GITEA_HTTP_REDIRECT_PORT]
         } else {
@@ -220,38 +302,36 @@ impl Image for Gitea {
         &self,
         _cs: ContainerState,
     ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        let exec = |cmd: Vec<String>| {
+            ExecCommand::new(cmd).with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+        };
         // Create admin user
-        let mut start_commands = vec![self.create_admin_user_cmd()];
+        let mut commands = vec![exec(self.create_admin_user_cmd())];
         // Add admins' public key if needed
         if let Some(key) = &self.admin_key {
-            start_commands.push(self.create_admin_key_cmd(key));
+            commands.push(exec(self.create_admin_key_cmd(key)));
         }
         // create repos if they're defined
         self.repos.iter().for_each(|r| {
-            start_commands.push(self.create_repo_cmd(r));
+            commands.push(exec(self.create_repo_cmd(r)));
+            if self.repo_ready_check {
+                commands.push(self.wait_for_repo_cmd(r));
+            }
+        });
+        // migrate external repos if they're defined
+        self.migrated_repos.iter().for_each(|r| {
+            commands.push(exec(self.migrate_repo_cmd(r)));
         });
 
         // and finally, add `gitea admin` commands, if defined
-        let admin_commands: Vec<Vec<String>> = self
-            .admin_commands
-            .clone()
-            .into_iter()
-            .map(|v| {
+        commands.extend(self.admin_commands.iter().map(|v| {
+            exec(
                 vec!["gitea".to_string(), "admin".to_string()]
                     .into_iter()
-                    .chain(v)
-                    .collect::<Vec<String>>()
-            })
-            .collect();
-
-        // glue everything togather
-        start_commands.extend(admin_commands);
-
-        // and convert to `ExecCommand`s
-        let commands: Vec<ExecCommand> = start_commands
-            .iter()
-            .map(|v| ExecCommand::new(v).with_cmd_ready_condition(CmdWaitFor::exit_code(0)))
-            .collect();
+                    .chain(v.iter().cloned())
+                    .collect(),
+            )
+        }));
 
         Ok(commands)
     }
@@ -289,6 +369,18 @@ impl Gitea {
         }
     }
 
+    /// Use another tag of the `gitea/gitea` image instead of the default `1.22.3-rootless`.
+    ///
+    /// Unlike [`ImageExt::with_tag`](testcontainers::ImageExt::with_tag), this keeps the other settings of the module.
+    /// The generated config assumes the paths of the rootless image, so only `-rootless` tags work.
+    /// Releases `1.22.x-rootless` are known to work.
+    pub fn with_tag(self, tag: impl Into<String>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            ..self
+        }
+    }
+
     /// Set git server hostname instead of the default `localhost`.
     ///
     /// This is not a containers' hostname, but the name which git server uses in various links like repo URLs.
@@ -304,6 +396,28 @@ impl Gitea {
         }
     }
 
+    /// Set the name of the instance (`APP_NAME`), which is shown e.g. in page titles,
+    /// instead of the default `Gitea: Git with a cup of tea`.
+    pub fn with_app_name(self, name: impl Into<String>) -> Self {
+        let new = Self {
+            app_name: Some(name.into()),
+            ..self
+        };
+        Self {
+            // to update app.ini
+            copy_to_sources: new.generate_copy_to_sources(),
+            ..new
+        }
+    }
+
+    /// Enable or disable [federation](https://docs.gitea.com/administration/config-cheat-sheet#federation-federation)
+    /// using ActivityPub, which is disabled by default.
+    ///
+    /// If enabled, the instance serves [NodeInfo](https://nodeinfo.diaspora.software/) at `/api/v1/nodeinfo`.
+    pub fn with_federation(self, enabled: bool) -> Self {
+        self.with_app_ini_value("federation", "ENABLED", enabled.to_string())
+    }
+
     /// Create a repository during startup.
     ///
     /// It's possible to call this method more than once to create several repositories.
@@ -327,6 +441,89 @@ impl Gitea {
         Self { repos, ..self }
     }
 
+    /// Wait for every repository created by [Gitea::with_repo()] to have its initial commit.
+    ///
+    /// Repositories are initialized with a README asynchronously, so right after startup a repository
+    /// may still be empty. With this check, the branches API of each repository is polled after its creation
+    /// until it lists a branch, so the repositories can be cloned as soon as the container is started.
+    /// Starting the container fails if a repository has no branch after about a minute.
+    pub fn with_repo_ready_check(self) -> Self {
+        Self {
+            repo_ready_check: true,
+            ..self
+        }
+    }
+
+    /// Store the SQLite database in `host_dir`, bind-mounted to [GITEA_SQLITE_FOLDER],
+    /// so `gitea.db` ([GITEA_SQLITE_FILE_NAME]) can be inspected on the host, e.g. for debugging.
+    ///
+    /// The rootless image doesn't run as root, so `host_dir` has to be writable by other users.
+    /// Only the database is mounted, repositories and other data stay in [GITEA_DATA_FOLDER].
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [Gitea::try_with_sqlite_mount()].
+    pub fn with_sqlite_mount(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_sqlite_mount(host_dir)
+            .expect("database directory is not valid UTF-8")
+    }
+
+    /// Same as [Gitea::with_sqlite_mount()], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_sqlite_mount(
+        self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        let new = Self {
+            sqlite_mount: Some(Mount::bind_mount(host_dir, GITEA_SQLITE_FOLDER)),
+            ..self
+        };
+        Ok(new.with_app_ini_value(
+            "database",
+            "PATH",
+            format!("{GITEA_SQLITE_FOLDER}/{GITEA_SQLITE_FILE_NAME}"),
+        ))
+    }
+
+    /// Set the name of the default branch of new repositories, e.g. `main`, instead of Gitea's default.
+    ///
+    /// This applies to repositories created by [Gitea::with_repo()] as well as to repositories created later.
+    pub fn with_default_branch(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        let gitea = self.with_app_ini_value("repository", "DEFAULT_BRANCH", &name);
+        Self {
+            default_branch: Some(name),
+            ..gitea
+        }
+    }
+
+    /// Import an existing git repository from `clone_url` as `name` during startup.
+    ///
+    /// The repository is migrated using the `/repos/migrate` API and owned by the admin user.
+    /// It's possible to call this method more than once to migrate several repositories.
+    ///
+    /// Note: by default Gitea refuses to migrate from local network addresses, e.g. other containers;
+    /// allow it with `with_env_var("GITEA__migrations__ALLOW_LOCALNETWORKS", "true")`,
+    /// see [Gitea::with_env_var()].
+    pub fn with_migrated_repo(
+        self,
+        clone_url: impl Into<String>,
+        name: impl Into<String>,
+        private: bool,
+    ) -> Self {
+        let mut migrated_repos = self.migrated_repos;
+        migrated_repos.push(GiteaMigratedRepo {
+            clone_url: clone_url.into(),
+            name: name.into(),
+            private,
+        });
+        Self {
+            migrated_repos,
+            ..self
+        }
+    }
+
     /// Add `gitea admin ...` command with parameters to execute after server startup.
     ///
     /// This method is useful, for example, to create additional users or to do other admin stuff.
@@ -374,6 +571,61 @@ impl Gitea {
         }
     }
 
+    /// Add an OAuth2 authentication source during startup, using `gitea admin auth add-oauth`.
+    ///
+    /// `provider` is one of the providers supported by `Gitea`, e.g. `openidConnect`, `github` or `gitlab`.
+    /// For the `openidConnect` provider, `auto_discover_url` should point to the
+    /// `.well-known/openid-configuration` endpoint of the identity provider,
+    /// which has to be reachable from the `Gitea` container.
+    ///
+    /// It's possible to call this method more than once to add several sources.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// #[tokio::test]
+    /// async fn test() {
+    ///     let gitea = Gitea::default()
+    ///         .with_oauth2_source(
+    ///             "dex",
+    ///             "openidConnect",
+    ///             "gitea-client",
+    ///             "gitea-secret",
+    ///             Some("http://dex:5556/dex/.well-known/openid-configuration"),
+    ///         )
+    ///         .start()
+    ///         .await
+    ///         .unwrap();
+    /// // ...
+    /// }
+    /// ```
+    pub fn with_oauth2_source(
+        self,
+        name: impl Into<String>,
+        provider: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        auto_discover_url: Option<&str>,
+    ) -> Self {
+        let mut command = vec![
+            "auth".to_string(),
+            "add-oauth".to_string(),
+            "--name".to_string(),
+            name.into(),
+            "--provider".to_string(),
+            provider.into(),
+            "--key".to_string(),
+            client_id.into(),
+            "--secret".to_string(),
+            client_secret.into(),
+        ];
+        if let Some(url) = auto_discover_url {
+            command.push("--auto-discover-url".to_string());
+            command.push(url.to_string());
+        }
+
+        self.with_admin_command(command)
+    }
+
     /// `Gitea` web server will start with HTTPS listener (with auto-generated certificate),
     /// instead of the default HTTP.
     ///
@@ -382,7 +634,7 @@ impl Gitea {
     /// it can be obtained by [Gitea::tls_ca()] method call.
     ///
     /// Note: _If TLS is enabled, additional HTTP listener will be started on port [GITEA_HTTP_REDIRECT_PORT]
-    /// to redirect all HTTP calls to the HTTPS listener._
+    /// to redirect all HTTP calls to the HTTPS listener, unless disabled by [Gitea::with_http_redirect()]._
     pub fn with_tls(self, enabled: bool) -> Self {
         let new = Self {
             tls: if enabled {
@@ -406,7 +658,7 @@ impl Gitea {
     /// This method is similar to [Gitea::with_tls()] but use provided certificate instead of generating self-signed one.
     ///
     /// Note: _If TLS is enabled, additional HTTP listener will be started on port [GITEA_HTTP_REDIRECT_PORT]
-    /// to redirect all HTTP calls to the HTTPS listener._
+    /// to redirect all HTTP calls to the HTTPS listener, unless disabled by [Gitea::with_http_redirect()]._
     pub fn with_tls_certs(self, cert: impl Into<String>, key: impl Into<String>) -> Self {
         let new = Self {
             tls: Some(GiteaTlsCert::from_pem(cert.into(), key.into())),
@@ -420,6 +672,117 @@ impl Gitea {
         }
     }
 
+    /// Enable or disable the HTTP listener on port [GITEA_HTTP_REDIRECT_PORT] which redirects
+    /// all HTTP calls to the HTTPS listener. It's enabled by default.
+    ///
+    /// Has no effect if TLS isn't enabled, since there is no HTTPS listener to redirect to.
+    pub fn with_http_redirect(self, enabled: bool) -> Self {
+        let new = Self {
+            http_redirect: enabled,
+            ..self
+        };
+        Self {
+            // to update app.ini
+            copy_to_sources: new.generate_copy_to_sources(),
+            ..new
+        }
+    }
+
+    /// Allow trivial passwords, e.g. for the admin account set by [Gitea::with_admin_account()].
+    ///
+    /// By default, `Gitea` requires passwords to be at least 8 characters long.
+    /// This method disables password complexity checks and lowers the minimal password length to a single character.
+    pub fn with_weak_passwords_allowed(self) -> Self {
+        self.with_app_ini_value("security", "PASSWORD_COMPLEXITY", "off")
+            .with_app_ini_value("security", "MIN_PASSWORD_LENGTH", "1")
+    }
+
+    /// Enable the [package registry](https://docs.gitea.com/usage/packages/overview).
+    ///
+    /// Packages can be published using the API, e.g. a generic package with
+    /// `PUT /api/packages/{owner}/generic/{package}/{version}/{file}`,
+    /// and are listed under `/api/v1/packages/{owner}`.
+    pub fn with_packages_enabled(self) -> Self {
+        self.with_app_ini_value("packages", "ENABLED", "true")
+    }
+
+    /// Limit the size of files uploaded to repositories, of attachments and of LFS objects to `bytes`.
+    ///
+    /// Files larger than `bytes` are still stored when committed via the contents API,
+    /// but their content is no longer returned by it (`DEFAULT_MAX_BLOB_SIZE` of the `[api]` section).
+    ///
+    /// Gitea configures upload and attachment limits in whole megabytes,
+    /// so these are rounded up; the LFS and API limits are exact.
+    /// Gitea reads a limit of `0` as unlimited, so `0` is raised to the smallest limit, i.e. one byte.
+    pub fn with_max_file_size(self, bytes: u64) -> Self {
+        let bytes = bytes.max(1);
+        let megabytes = bytes.div_ceil(1024 * 1024).to_string();
+        self.with_app_ini_value("repository.upload", "FILE_MAX_SIZE", &megabytes)
+            .with_app_ini_value("attachment", "MAX_SIZE", megabytes)
+            .with_app_ini_value("server", "LFS_MAX_FILE_SIZE", bytes.to_string())
+            .with_app_ini_value("api", "DEFAULT_MAX_BLOB_SIZE", bytes.to_string())
+    }
+
+    /// Set the `SECRET_KEY` of the `[security]` section, which Gitea otherwise generates on the first start.
+    ///
+    /// The key encrypts secrets stored in the database, e.g. authorization headers of webhooks,
+    /// so with a fixed key they remain readable by another container using the same database,
+    /// see [Gitea::with_sqlite_mount()].
+    pub fn with_secret_key(self, secret: impl Into<String>) -> Self {
+        self.with_app_ini_value("security", "SECRET_KEY", secret)
+    }
+
+    /// Store sessions in the database (`PROVIDER = db` of the `[session]` section)
+    /// instead of files in the container.
+    ///
+    /// Combined with [Gitea::with_sqlite_mount()] and [Gitea::with_secret_key()],
+    /// session cookies remain valid in another container using the same database.
+    pub fn with_db_sessions(self) -> Self {
+        self.with_app_ini_value("session", "PROVIDER", "db")
+    }
+
+    /// Set the `INTERNAL_TOKEN` of the `[security]` section, used by the git hooks and CLI
+    /// to authenticate to the internal API, instead of the fixed token of the module's app.ini.
+    pub fn with_internal_token(self, token: impl Into<String>) -> Self {
+        self.with_app_ini_value("security", "INTERNAL_TOKEN", token)
+    }
+
+    /// Enable the Prometheus `/metrics` endpoint.
+    ///
+    /// If a `token` is set, the endpoint requires it as bearer token (`Authorization: Bearer <token>`).
+    pub fn with_metrics(self, token: Option<String>) -> Self {
+        let gitea = self.with_app_ini_value("metrics", "ENABLED", "true");
+        match token {
+            Some(token) => gitea.with_app_ini_value("metrics", "TOKEN", token),
+            None => gitea,
+        }
+    }
+
+    /// Disable all [cron tasks](https://docs.gitea.com/administration/config-cheat-sheet#cron-cron),
+    /// so no background jobs (e.g. repository health checks or cleanups) change the state during tests.
+    pub fn with_cron_disabled(self) -> Self {
+        DEFAULT_CRON_TASKS.iter().fold(
+            self.with_app_ini_value("cron", "ENABLED", "false"),
+            |gitea, task| gitea.with_app_ini_value(format!("cron.{task}"), "ENABLED", "false"),
+        )
+    }
+
+    /// Add an environment variable to the container.
+    ///
+    /// Variables in the `GITEA__section__KEY` form are applied to app.ini by the image on startup,
+    /// which allows to change settings without a dedicated method, e.g. `GITEA__mailer__ENABLED`.
+    /// Dots in section names are written as `_0X2E_`, e.g. `GITEA__repository_0X2E_upload__ENABLED`.
+    ///
+    /// Hostname, protocol and TLS settings of the `[server]` section are managed by the module
+    /// (see [Gitea::with_git_hostname()] and [Gitea::with_tls()]), so variables for them are ignored.
+    ///
+    /// It's possible to call this method more than once to add several variables.
+    pub fn with_env_var(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let mut env_vars = self.env_vars;
+        env_vars.insert(key.into(), value.into());
+        Self { env_vars, ..self }
+    }
+
     /// Return PEM encoded Root CA certificate of the Gitea servers' certificate issuer.
     ///
     /// If TLS has been enabled using [Gitea::with_tls_certs()] method (with auto-generated self-signed certificate),
@@ -433,17 +796,56 @@ impl Gitea {
         self.tls.as_ref().and_then(|t| t.ca())
     }
 
+    /// Return how TLS of the web server is configured.
+    ///
+    /// Unlike [Gitea::tls_ca()], this distinguishes disabled TLS from TLS with an external certificate.
+    pub fn tls_mode(&self) -> TlsMode {
+        match &self.tls {
+            None => TlsMode::Off,
+            Some(tls) if tls.ca().is_some() => TlsMode::Generated,
+            Some(_) => TlsMode::External,
+        }
+    }
+
+    /// Returns the base URL of the web server, e.g. `http://127.0.0.1:3000`,
+    /// with `host_port` being the host port [`GITEA_HTTP_PORT`] is mapped to.
+    /// The scheme is `https` if enabled via [Gitea::with_tls()] or [Gitea::with_tls_certs()].
+    ///
+    /// See [`url`] and [`url_blocking`] to get the URL of a running container.
+    pub fn url(&self, host: impl Display, host_port: u16) -> String {
+        format!("{}://{host}:{host_port}", self.protocol())
+    }
+
+    /// Set `key` in the `section` of app.ini to `value`.
+    fn with_app_ini_value(
+        self,
+        section: impl Into<String>,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        let mut app_ini_config = self.app_ini_config;
+        app_ini_config
+            .entry(section.into())
+            .or_default()
+            .insert(key.into(), value.into());
+
+        let new = Self {
+            app_ini_config,
+            ..self
+        };
+        Self {
+            // to update app.ini
+            copy_to_sources: new.generate_copy_to_sources(),
+            ..new
+        }
+    }
+
     /// Gather app.ini and certificates (if needed) into one vector to store into modules' structure.
     fn generate_copy_to_sources(&self) -> Vec<CopyToContainer> {
         let mut to_copy = vec![];
 
         // Prepare app.ini from template
-        let app_ini = Self::render_app_ini(
-            self.protocol(),
-            self.git_hostname.as_str(),
-            self.tls.is_some(),
-        );
-        to_copy.push(app_ini);
+        to_copy.push(self.render_app_ini());
 
         // Add certificates if TLS is enabled
         if let Some(tls_config) = &self.tls {
@@ -463,12 +865,21 @@ impl Gitea {
     }
 
     /// Render app.ini content from the template using current config values.
-    fn render_app_ini(protocol: &str, hostname: &str, is_tls: bool) -> CopyToContainer {
+    fn render_app_ini(&self) -> CopyToContainer {
+        let protocol = self.protocol();
+        let hostname = self.git_hostname.as_str();
         let redirect_port = GITEA_HTTP_REDIRECT_PORT.as_u16();
         // load template of the app.ini,
         // `[server]` section should be at the bottom to add variable part
         // and TLS-related variables is needed
         let mut app_ini_template = include_str!("app.ini").to_string();
+        if let Some(app_name) = &self.app_name {
+            app_ini_template = app_ini_template.replacen(
+                DEFAULT_APP_NAME_LINE,
+                &format!("APP_NAME = {app_name}"),
+                1,
+            );
+        }
         let host_template_part = format!(
             r#"
 DOMAIN = {hostname}
@@ -480,17 +891,32 @@ PROTOCOL = {protocol}
         app_ini_template.push_str(&host_template_part);
 
         // If TLS is enabled, add TLS-related config to app.ini
-        if is_tls {
+        if self.tls.is_some() {
             let tls_config = format!(
                 r#"
 CERT_FILE = {GITEA_CONFIG_FOLDER}/{TLS_CERT_FILE_NAME}
 KEY_FILE = {GITEA_CONFIG_FOLDER}/{TLS_KEY_FILE_NAME}
-REDIRECT_OTHER_PORT = true
-PORT_TO_REDIRECT = {redirect_port}
 "#
             );
             app_ini_template.push_str(&tls_config);
         }
+        if self.redirects_http() {
+            let redirect_config = format!(
+                r#"REDIRECT_OTHER_PORT = true
+PORT_TO_REDIRECT = {redirect_port}
+"#
+            );
+            app_ini_template.push_str(&redirect_config);
+        }
+
+        // Additional sections go after `[server]`,
+        // sections which are already in the template are merged by Gitea.
+        for (section, values) in &self.app_ini_config {
+            app_ini_template.push_str(&format!("\n[{section}]\n"));
+            for (key, value) in values {
+                app_ini_template.push_str(&format!("{key} = {value}\n"));
+            }
+        }
 
         CopyToContainer::new(
             CopyDataSource::Data(app_ini_template.into_bytes()),
@@ -527,18 +953,50 @@ PORT_TO_REDIRECT = {redirect_port}
     /// Generate curl command with API call to create repository with minimal parameters.
     fn create_repo_cmd(&self, repo: &GiteaRepo) -> Vec<String> {
         let (repo, private) = match repo {
-            GiteaRepo::Private(name) => (name, "true"),
-            GiteaRepo::Public(name) => (name, "false"),
+            GiteaRepo::Private(name) => (name, true),
+            GiteaRepo::Public(name) => (name, false),
         };
 
+        let default_branch = self
+            .default_branch
+            .as_deref()
+            .map(|branch| format!(r#","default_branch":{}"#, json_string(branch)))
+            .unwrap_or_default();
         let body = format!(
-            r#"{{"name":"{}","readme":"Default","auto_init":true,"private":{}}}"#,
-            repo, private
+            r#"{{"name":{},"readme":"Default","auto_init":true,"private":{private}{default_branch}}}"#,
+            json_string(repo)
         );
 
         self.create_gitea_api_curl_cmd("POST", "/user/repos", Some(body))
     }
 
+    /// Generate command polling the branches of the created repository until there is one.
+    fn wait_for_repo_cmd(&self, repo: &GiteaRepo) -> ExecCommand {
+        let (GiteaRepo::Private(repo) | GiteaRepo::Public(repo)) = repo;
+        // fails after 300 attempts, i.e. about a minute
+        retry_until_success(
+            BRANCHES_PROBE,
+            [
+                format!("{}:{}", self.admin_username, self.admin_password),
+                self.api_url(&format!("/repos/{}/{repo}/branches", self.admin_username)),
+            ],
+            300,
+            Duration::from_millis(200),
+        )
+    }
+
+    /// Generate curl command with API call to migrate an external repository.
+    fn migrate_repo_cmd(&self, repo: &GiteaMigratedRepo) -> Vec<String> {
+        let body = format!(
+            r#"{{"clone_addr":{},"repo_name":{},"private":{},"service":"git"}}"#,
+            json_string(&repo.clone_url),
+            json_string(&repo.name),
+            repo.private
+        );
+
+        self.create_gitea_api_curl_cmd("POST", "/repos/migrate", Some(body))
+    }
+
     /// Helper to generate curl commands with API call.
     fn create_gitea_api_curl_cmd(
         &self,
@@ -574,6 +1032,11 @@ PORT_TO_REDIRECT = {redirect_port}
         curl
     }
 
+    /// Whether HTTP listener to redirect calls to HTTPS is started.
+    fn redirects_http(&self) -> bool {
+        self.tls.is_some() && self.http_redirect
+    }
+
     /// Return configured protocol string.
     fn protocol(&self) -> &str {
         if self.tls.is_some() {
@@ -594,6 +1057,37 @@ PORT_TO_REDIRECT = {redirect_port}
     }
 }
 
+/// Returns the [`Gitea::url`] of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`GITEA_HTTP_PORT`].
+pub async fn url(container: &ContainerAsync<Gitea>) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(GITEA_HTTP_PORT).await?;
+    Ok(container.image().url(host, port))
+}
+
+/// Returns the [`Gitea::url`] of a container started with the `SyncRunner`,
+/// using the host port mapped to [`GITEA_HTTP_PORT`].
+#[cfg(feature = "blocking")]
+pub fn url_blocking(container: &Container<Gitea>) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(GITEA_HTTP_PORT)?;
+    Ok(container.image().url(host, port))
+}
+
+/// Whether `env_var` overrides one of the [RENDERED_SERVER_KEYS].
+fn is_rendered_server_key(env_var: &str) -> bool {
+    let Some((section, key)) = env_var
+        .strip_prefix("GITEA__")
+        .and_then(|s| s.split_once("__"))
+    else {
+        return false;
+    };
+    section.eq_ignore_ascii_case("server")
+        && RENDERED_SERVER_KEYS
+            .iter()
+            .any(|k| k.eq_ignore_ascii_case(key))
+}
+
 /// Defines repository to create during container startup.
 ///
 /// Each option includes repository name in the enum value.
@@ -607,6 +1101,25 @@ pub enum GiteaRepo {
     Public(String),
 }
 
+/// TLS configuration of the `Gitea` web server, returned by [`Gitea::tls_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TlsMode {
+    /// TLS is disabled, the server listens for HTTP requests.
+    Off,
+    /// TLS is enabled with an auto-generated self-signed certificate, see [`Gitea::with_tls`].
+    Generated,
+    /// TLS is enabled with a certificate provided by [`Gitea::with_tls_certs`].
+    External,
+}
+
+/// External repository to migrate during container startup.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct GiteaMigratedRepo {
+    clone_url: String,
+    name: String,
+    private: bool,
+}
+
 /// Helper struct to store TLS certificates.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct GiteaTlsCert {
@@ -628,34 +1141,12 @@ impl GiteaTlsCert {
     /// SAN list includes "localhost", "127.0.0.1", "::1"
     /// and provided hostname (if it's different form localhost).
     fn new(hostname: impl Into<String>) -> Self {
-        // generate root CA key and cert
-        let ca_key = KeyPair::generate().unwrap();
-        let mut ca_cert = CertificateParams::new(vec!["Gitea root CA".to_string()]).unwrap();
-        ca_cert.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
-        let ca_cert = ca_cert.self_signed(&ca_key).unwrap();
-
-        // prepare SANs
-        let mut hostnames = vec![
-            "localhost".to_string(),
-            "127.0.0.1".to_string(),
-            "::1".to_string(),
-        ];
-        let hostname = hostname.into();
-        if hostname != "localhost" {
-            hostnames.insert(0, hostname);
-        }
-
-        // and generate server key and cert
-        let key = KeyPair::generate().unwrap();
-        let cert = CertificateParams::new(hostnames)
-            .unwrap()
-            .signed_by(&key, &ca_cert, &ca_key)
-            .unwrap();
-
+        let hostname: String = hostname.into();
+        let GeneratedCert { cert, key, ca } = GeneratedCert::new("Gitea root CA", &[&hostname]);
         Self {
-            cert: cert.pem(),
-            key: key.serialize_pem(),
-            ca: Some(ca_cert.pem()),
+            cert,
+            key,
+            ca: Some(ca),
         }
     }
 
@@ -674,13 +1165,47 @@ impl GiteaTlsCert {
     }
 }
 
+impl ImageReference for Gitea {}
+
+impl HealthProbe for Gitea {
+    /// The `/api/healthz` endpoint reports the web server and the database as healthy.
+    fn health_probe(&self) -> Vec<String> {
+        vec![
+            "curl".to_owned(),
+            "-sfk".to_owned(),
+            format!(
+                "{}://localhost:{}/api/healthz",
+                self.protocol(),
+                GITEA_HTTP_PORT.as_u16()
+            ),
+        ]
+    }
+}
+
+/// Quotes `value` as a JSON string, escaping quotes, backslashes and control characters.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 #[cfg(test)]
 mod tests {
     use reqwest::Certificate;
     use serde_json::Value;
-    use testcontainers::{runners::AsyncRunner, ContainerAsync};
+    use testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt};
 
     use super::*;
+    use crate::ModuleHealthExt;
 
     const TEST_PUBLIC_KEY: &str =
         "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIJRE5a67/cTbR6DpWqzBl6BTY0LE0Hg715ZI/FMK7iCH";
@@ -689,15 +1214,97 @@ mod tests {
     const TEST_PUBLIC_REPO: &str = "test-public-repo";
     const TEST_PRIVATE_REPO: &str = "test-private-repo";
 
+    #[test]
+    fn gitea_image_reference() {
+        let gitea = Gitea::default();
+        assert_eq!(gitea.image_reference(), "gitea/gitea:1.22.3-rootless");
+        assert_eq!(
+            gitea.image_reference(),
+            format!("{}:{}", gitea.name(), gitea.tag())
+        );
+    }
+
+    #[tokio::test]
+    async fn gitea_custom_tag() {
+        let gitea = Gitea::default()
+            .with_tag("1.22.2-rootless")
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(gitea.image().tag(), "1.22.2-rootless");
+
+        let response = reqwest::get(api_url(&gitea, "/version").await)
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+        assert_eq!(response["version"], "1.22.2");
+
+        let host = gitea.get_host().await.unwrap();
+        let port = gitea.get_host_port_ipv4(GITEA_HTTP_PORT).await.unwrap();
+        let response = reqwest::get(format!("http://{host}:{port}/api/swagger"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn gitea_sqlite_mount_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/gitea-\xff"));
+        let result = Gitea::default().try_with_sqlite_mount(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
+    #[test]
+    fn gitea_tls_mode() {
+        assert_eq!(Gitea::default().tls_mode(), TlsMode::Off);
+        assert_eq!(Gitea::default().with_tls(false).tls_mode(), TlsMode::Off);
+        assert_eq!(
+            Gitea::default().with_tls(true).tls_mode(),
+            TlsMode::Generated
+        );
+        assert_eq!(
+            Gitea::default().with_tls_certs("cert", "key").tls_mode(),
+            TlsMode::External
+        );
+    }
+
     async fn api_url(container: &ContainerAsync<Gitea>, api: &str) -> String {
         let api = api.strip_prefix('/').unwrap_or(api);
-        let host = container.get_host().await.unwrap();
-        let port = container.get_host_port_ipv4(GITEA_HTTP_PORT).await.unwrap();
+        format!("{}/api/v1/{api}", url(container).await.unwrap())
+    }
 
-        format!(
-            "{}://{host}:{port}/api/v1/{api}",
-            container.image().protocol(),
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn gitea_url_blocking() {
+        use std::io::{Read, Write};
+
+        use testcontainers::runners::SyncRunner;
+
+        let gitea = SyncRunner::start(Gitea::default()).unwrap();
+        let url = url_blocking(&gitea).unwrap();
+        let host = gitea.get_host().unwrap();
+        let port = gitea.get_host_port_ipv4(GITEA_HTTP_PORT).unwrap();
+        assert_eq!(url, format!("http://{host}:{port}"));
+
+        // plain HTTP request, to not depend on an async runtime
+        let address = url.strip_prefix("http://").unwrap();
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "GET /api/healthz HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n"
         )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
     }
 
     #[tokio::test]
@@ -735,6 +1342,35 @@ mod tests {
         assert!(keys_list.is_empty());
     }
 
+    #[tokio::test]
+    async fn gitea_start_healthy() {
+        let gitea = Gitea::default()
+            .with_tls(true)
+            .start_healthy()
+            .await
+            .unwrap();
+
+        let ca = Certificate::from_pem(gitea.image().tls_ca().unwrap().as_bytes()).unwrap();
+        let client = reqwest::ClientBuilder::new()
+            .use_rustls_tls()
+            .add_root_certificate(ca)
+            .build()
+            .unwrap();
+        // no retries needed, the probe has passed already
+        let response = client
+            .get(
+                api_url(&gitea, "")
+                    .await
+                    .replace("/api/v1/", "/api/healthz"),
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let health = response.json::<Value>().await.unwrap();
+        assert_eq!(health["status"], "pass");
+    }
+
     #[tokio::test]
     async fn gitea_with_tls() {
         let gitea = Gitea::default().with_tls(true).start().await.unwrap();
@@ -922,4 +1558,712 @@ mod tests {
         let response = response.as_array().unwrap();
         assert_eq!(response.len(), 1);
     }
+
+    #[tokio::test]
+    async fn gitea_oauth2_source() {
+        let gitea = Gitea::default()
+            .with_oauth2_source(
+                "test-sso",
+                "github",
+                "test-client-id",
+                "test-client-secret",
+                None,
+            )
+            .start()
+            .await
+            .unwrap();
+
+        let mut result = gitea
+            .exec(ExecCommand::new(["gitea", "admin", "auth", "list"]))
+            .await
+            .unwrap();
+        let stdout = String::from_utf8(result.stdout_to_vec().await.unwrap()).unwrap();
+
+        assert!(stdout.contains("test-sso"));
+        assert!(stdout.contains("OAuth2"));
+    }
+
+    #[tokio::test]
+    async fn gitea_weak_admin_password() {
+        let gitea = Gitea::default()
+            .with_admin_account(TEST_ADMIN_USERNAME, "test", None)
+            .with_weak_passwords_allowed()
+            .start()
+            .await
+            .unwrap();
+
+        let response = reqwest::Client::new()
+            .get(api_url(&gitea, "/user").await)
+            .basic_auth(TEST_ADMIN_USERNAME, Some("test"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn gitea_packages() {
+        let gitea = Gitea::default()
+            .with_packages_enabled()
+            .start()
+            .await
+            .unwrap();
+
+        let host = gitea.get_host().await.unwrap();
+        let port = gitea.get_host_port_ipv4(GITEA_HTTP_PORT).await.unwrap();
+        let response = reqwest::Client::new()
+            .put(format!(
+                "http://{host}:{port}/api/packages/{GITEA_DEFAULT_ADMIN_USERNAME}/generic/test-package/1.0.0/test.txt"
+            ))
+            .basic_auth(
+                GITEA_DEFAULT_ADMIN_USERNAME,
+                Some(GITEA_DEFAULT_ADMIN_PASSWORD),
+            )
+            .body("test content")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 201);
+
+        let packages = reqwest::Client::new()
+            .get(api_url(&gitea, &format!("/packages/{GITEA_DEFAULT_ADMIN_USERNAME}")).await)
+            .basic_auth(
+                GITEA_DEFAULT_ADMIN_USERNAME,
+                Some(GITEA_DEFAULT_ADMIN_PASSWORD),
+            )
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+
+        let packages = packages.as_array().unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0]["name"], "test-package");
+    }
+
+    #[tokio::test]
+    async fn gitea_migrated_repo() {
+        let network = format!("gitea-migration-{}", std::process::id());
+        let source_name = format!("gitea-source-{}", std::process::id());
+
+        let _source = Gitea::default()
+            .with_repo(GiteaRepo::Public(TEST_PUBLIC_REPO.to_string()))
+            .with_network(&network)
+            .with_container_name(&source_name)
+            .start()
+            .await
+            .unwrap();
+        let gitea = Gitea::default()
+            .with_migrated_repo(
+                format!("http://{source_name}:3000/{GITEA_DEFAULT_ADMIN_USERNAME}/{TEST_PUBLIC_REPO}.git"),
+                "migrated",
+                false,
+            )
+            .with_env_var("GITEA__migrations__ALLOW_LOCALNETWORKS", "true")
+            .with_network(&network)
+            .start()
+            .await
+            .unwrap();
+
+        let repo = reqwest::Client::new()
+            .get(
+                api_url(
+                    &gitea,
+                    &format!("/repos/{GITEA_DEFAULT_ADMIN_USERNAME}/migrated"),
+                )
+                .await,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+
+        assert_eq!(repo["owner"]["login"], GITEA_DEFAULT_ADMIN_USERNAME);
+        assert_eq!(repo["empty"], false);
+    }
+
+    #[tokio::test]
+    async fn gitea_max_file_size() {
+        let gitea = Gitea::default()
+            .with_repo(GiteaRepo::Public(TEST_PUBLIC_REPO.to_string()))
+            .with_max_file_size(2 * 1024 * 1024)
+            .start()
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let contents_url = api_url(
+            &gitea,
+            &format!("/repos/{GITEA_DEFAULT_ADMIN_USERNAME}/{TEST_PUBLIC_REPO}/contents"),
+        )
+        .await;
+        // base64 of "aaa", so the content of a file of `size` bytes is repeated `size / 3` times
+        let content = |size: usize| "YWFh".repeat(size / 3);
+
+        for (path, size) in [
+            ("small.txt", 2 * 1024 * 1024 - 1025),
+            ("large.txt", 3 * 1024 * 1024),
+        ] {
+            let response = client
+                .post(format!("{contents_url}/{path}"))
+                .basic_auth(
+                    GITEA_DEFAULT_ADMIN_USERNAME,
+                    Some(GITEA_DEFAULT_ADMIN_PASSWORD),
+                )
+                .json(&serde_json::json!({ "content": content(size), "message": path }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 201);
+        }
+
+        let read = |path: &str| client.get(format!("{contents_url}/{path}")).send();
+
+        let small = read("small.txt")
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+        assert_eq!(small["size"], 2 * 1024 * 1024 - 1025);
+        assert_eq!(small["content"], content(2 * 1024 * 1024 - 1025));
+
+        let large = read("large.txt")
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+        assert_eq!(large["size"], 3 * 1024 * 1024);
+        assert!(large["content"].as_str().unwrap_or_default().is_empty());
+    }
+
+    #[tokio::test]
+    async fn gitea_metrics() {
+        let gitea = Gitea::default()
+            .with_metrics(Some("metrics-token".to_string()))
+            .start()
+            .await
+            .unwrap();
+
+        let host = gitea.get_host().await.unwrap();
+        let port = gitea.get_host_port_ipv4(GITEA_HTTP_PORT).await.unwrap();
+        let metrics_url = format!("http://{host}:{port}/metrics");
+
+        let response = reqwest::get(&metrics_url).await.unwrap();
+        assert_eq!(response.status(), 401);
+
+        let metrics = reqwest::Client::new()
+            .get(&metrics_url)
+            .bearer_auth("metrics-token")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(metrics.contains("gitea_organizations"));
+    }
+
+    #[tokio::test]
+    async fn gitea_cron_disabled() {
+        let gitea = Gitea::default()
+            .with_admin_account(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD, None)
+            .with_cron_disabled()
+            .start()
+            .await
+            .unwrap();
+
+        let mut app_ini = gitea
+            .exec(ExecCommand::new([
+                "cat",
+                &format!("{GITEA_CONFIG_FOLDER}/{CONFIG_FILE_NAME}"),
+            ]))
+            .await
+            .unwrap();
+        let app_ini = String::from_utf8(app_ini.stdout_to_vec().await.unwrap()).unwrap();
+        assert!(app_ini.contains("[cron]\nENABLED = false\n"));
+
+        // registered tasks are listed by the admin API, but disabled ones aren't scheduled
+        let tasks = reqwest::Client::new()
+            .get(api_url(&gitea, "/admin/cron").await)
+            .basic_auth(TEST_ADMIN_USERNAME, Some(TEST_ADMIN_PASSWORD))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<Vec<Value>>()
+            .await
+            .unwrap();
+        let scheduled = tasks
+            .iter()
+            .filter(|task| task["schedule"] != "-")
+            .map(|task| task["name"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert!(scheduled.is_empty(), "scheduled cron tasks: {scheduled:?}");
+
+        let response = reqwest::Client::new()
+            .post(api_url(&gitea, "/user/repos").await)
+            .basic_auth(TEST_ADMIN_USERNAME, Some(TEST_ADMIN_PASSWORD))
+            .json(&serde_json::json!({ "name": TEST_PUBLIC_REPO }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 201);
+    }
+
+    #[test]
+    fn gitea_migrate_repo_body_is_escaped() {
+        let repo = GiteaMigratedRepo {
+            clone_url: r#"https://example.com/a"b\c.git"#.to_string(),
+            name: r#"repo "quoted""#.to_string(),
+            private: true,
+        };
+        let cmd = Gitea::default().migrate_repo_cmd(&repo);
+        let body = &cmd[cmd.iter().position(|arg| arg == "-d").unwrap() + 1];
+        let body = serde_json::from_str::<Value>(body).unwrap();
+        assert_eq!(body["clone_addr"], repo.clone_url);
+        assert_eq!(body["repo_name"], repo.name);
+        assert_eq!(body["private"], true);
+    }
+
+    #[test]
+    fn gitea_create_repo_body_is_escaped() {
+        let cmd = Gitea::default()
+            .with_default_branch(r#"feature/"quoted"\branch"#)
+            .create_repo_cmd(&GiteaRepo::Private("private-repo".to_string()));
+        let body = &cmd[cmd.iter().position(|arg| arg == "-d").unwrap() + 1];
+        let body = serde_json::from_str::<Value>(body).unwrap();
+        assert_eq!(body["name"], "private-repo");
+        assert_eq!(body["private"], true);
+        assert_eq!(body["default_branch"], r#"feature/"quoted"\branch"#);
+
+        let cmd = Gitea::default()
+            .with_default_branch("tab\tline\nbreak")
+            .create_repo_cmd(&GiteaRepo::Public("public-repo".to_string()));
+        let body = &cmd[cmd.iter().position(|arg| arg == "-d").unwrap() + 1];
+        let body = serde_json::from_str::<Value>(body).unwrap();
+        assert_eq!(body["name"], "public-repo");
+        assert_eq!(body["private"], false);
+        assert_eq!(body["auto_init"], true);
+        assert_eq!(body["default_branch"], "tab\tline\nbreak");
+
+        let cmd = Gitea::default().create_repo_cmd(&GiteaRepo::Public("public-repo".to_string()));
+        let body = &cmd[cmd.iter().position(|arg| arg == "-d").unwrap() + 1];
+        let body = serde_json::from_str::<Value>(body).unwrap();
+        assert!(body.get("default_branch").is_none());
+    }
+
+    #[test]
+    fn gitea_max_file_size_zero_is_not_unlimited() {
+        let gitea = Gitea::default().with_max_file_size(0);
+        let value = |section: &str, key: &str| gitea.app_ini_config[section][key].as_str();
+        assert_eq!(value("repository.upload", "FILE_MAX_SIZE"), "1");
+        assert_eq!(value("attachment", "MAX_SIZE"), "1");
+        assert_eq!(value("server", "LFS_MAX_FILE_SIZE"), "1");
+        assert_eq!(value("api", "DEFAULT_MAX_BLOB_SIZE"), "1");
+    }
+
+    #[test]
+    fn gitea_secret_key_keeps_session_provider() {
+        let gitea = Gitea::default().with_secret_key("test-secret-key");
+        assert_eq!(
+            gitea.app_ini_config["security"]["SECRET_KEY"],
+            "test-secret-key"
+        );
+        assert!(!gitea
+            .app_ini_config
+            .get("session")
+            .is_some_and(|session| session.contains_key("PROVIDER")));
+
+        let gitea = gitea.with_db_sessions();
+        assert_eq!(gitea.app_ini_config["session"]["PROVIDER"], "db");
+    }
+
+    #[test]
+    fn gitea_env_vars_keep_rendered_server_keys() {
+        let gitea = Gitea::default()
+            .with_env_var("GITEA__server__PROTOCOL", "https")
+            .with_env_var("GITEA__SERVER__root_url", "https://example.com/")
+            .with_env_var("GITEA__server__LANDING_PAGE", "explore")
+            .with_env_var("GITEA__mailer__ENABLED", "true");
+        let env_vars = gitea
+            .env_vars()
+            .into_iter()
+            .map(|(k, v)| (k.into().into_owned(), v.into().into_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            env_vars,
+            vec![
+                ("GITEA__mailer__ENABLED".to_string(), "true".to_string()),
+                (
+                    "GITEA__server__LANDING_PAGE".to_string(),
+                    "explore".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn gitea_env_var() {
+        let gitea = Gitea::default()
+            .with_env_var("GITEA__api__DEFAULT_PAGING_NUM", "7")
+            // managed by the module, has to be ignored
+            .with_env_var("GITEA__server__PROTOCOL", "https")
+            .start()
+            .await
+            .unwrap();
+
+        // still served over plain HTTP
+        let response = reqwest::get(api_url(&gitea, "/settings/api").await)
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+        assert_eq!(response["default_paging_num"], 7);
+    }
+
+    #[tokio::test]
+    async fn gitea_with_tls_without_http_redirect() {
+        let gitea = Gitea::default()
+            .with_tls(true)
+            .with_http_redirect(false)
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(
+            gitea.image().expose_ports(),
+            &[GITEA_SSH_PORT, GITEA_HTTP_PORT]
+        );
+        assert!(gitea
+            .get_host_port_ipv4(GITEA_HTTP_REDIRECT_PORT)
+            .await
+            .is_err());
+
+        let mut app_ini = gitea
+            .exec(ExecCommand::new([
+                "cat",
+                &format!("{GITEA_CONFIG_FOLDER}/{CONFIG_FILE_NAME}"),
+            ]))
+            .await
+            .unwrap();
+        let app_ini = String::from_utf8(app_ini.stdout_to_vec().await.unwrap()).unwrap();
+        assert!(!app_ini.contains("REDIRECT_OTHER_PORT"));
+
+        let ca = gitea.image().tls_ca().unwrap();
+        let client = reqwest::ClientBuilder::new()
+            .use_rustls_tls()
+            .add_root_certificate(Certificate::from_pem(ca.as_bytes()).unwrap())
+            .build()
+            .unwrap();
+        let response = client
+            .get(api_url(&gitea, "/version").await)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn gitea_default_branch() {
+        let gitea = Gitea::default()
+            .with_default_branch("main")
+            .with_repo(GiteaRepo::Public(TEST_PUBLIC_REPO.to_string()))
+            .start()
+            .await
+            .unwrap();
+
+        let response = reqwest::get(
+            api_url(
+                &gitea,
+                &format!("/repos/{GITEA_DEFAULT_ADMIN_USERNAME}/{TEST_PUBLIC_REPO}"),
+            )
+            .await,
+        )
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap();
+        assert_eq!(response["default_branch"], "main");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn gitea_sqlite_mount() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let db_dir = std::env::temp_dir().join(format!("gitea-db-{}", std::process::id()));
+        std::fs::create_dir_all(&db_dir).unwrap();
+        // the server doesn't run as root
+        std::fs::set_permissions(&db_dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+        let gitea = Gitea::default()
+            .with_sqlite_mount(&db_dir)
+            .with_repo(GiteaRepo::Public(TEST_PUBLIC_REPO.to_string()))
+            .start()
+            .await
+            .unwrap();
+
+        let db = std::fs::read(db_dir.join(GITEA_SQLITE_FILE_NAME)).unwrap();
+        assert!(!db.is_empty());
+        // the repository table stores the name as plain text
+        assert!(db
+            .windows(TEST_PUBLIC_REPO.len())
+            .any(|window| window == TEST_PUBLIC_REPO.as_bytes()));
+
+        gitea.rm().await.unwrap();
+        // the database is owned by the user of the container, so this may fail
+        let _ = std::fs::remove_dir_all(&db_dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn gitea_session_survives_restart() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let db_dir = std::env::temp_dir().join(format!("gitea-session-db-{}", std::process::id()));
+        std::fs::create_dir_all(&db_dir).unwrap();
+        // the server doesn't run as root
+        std::fs::set_permissions(&db_dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+        // the admin of the database exists already, so each container creates its own one
+        async fn start(db_dir: &Path, admin: &str) -> ContainerAsync<Gitea> {
+            Gitea::default()
+                .with_admin_account(admin, TEST_ADMIN_PASSWORD, None)
+                .with_secret_key("test-secret-key")
+                .with_db_sessions()
+                .with_internal_token("test-internal-token")
+                .with_sqlite_mount(db_dir)
+                .start()
+                .await
+                .unwrap()
+        }
+        async fn web_url(gitea: &ContainerAsync<Gitea>, path: &str) -> String {
+            let api = api_url(gitea, "").await;
+            format!("{}{path}", api.trim_end_matches("/api/v1/"))
+        }
+        fn cookies(response: &reqwest::Response) -> Vec<String> {
+            response
+                .headers()
+                .get_all(reqwest::header::SET_COOKIE)
+                .iter()
+                .filter_map(|c| c.to_str().ok()?.split(';').next().map(String::from))
+                .collect()
+        }
+        // the settings redirect to the login page without a valid session
+        async fn signed_in(client: &reqwest::Client, url: String, cookie: &str) -> bool {
+            let response = client
+                .get(url)
+                .header(reqwest::header::COOKIE, cookie)
+                .send()
+                .await
+                .unwrap();
+            let location = response.headers().get(reqwest::header::LOCATION);
+            response.status().is_success()
+                || location.is_some_and(|l| !l.to_str().unwrap().contains("/user/login"))
+        }
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        let gitea = start(&db_dir, TEST_ADMIN_USERNAME).await;
+
+        // sign in with the login form
+        let login_page = client
+            .get(web_url(&gitea, "/user/login").await)
+            .send()
+            .await
+            .unwrap();
+        let mut login_cookies = cookies(&login_page);
+        let html = login_page.text().await.unwrap();
+        let csrf = html
+            .split(r#"name="_csrf" value=""#)
+            .nth(1)
+            .and_then(|s| s.split('"').next())
+            .unwrap()
+            .to_string();
+        let login = client
+            .post(web_url(&gitea, "/user/login").await)
+            .header(reqwest::header::COOKIE, login_cookies.join("; "))
+            .form(&[
+                ("_csrf", csrf.as_str()),
+                ("user_name", TEST_ADMIN_USERNAME),
+                ("password", TEST_ADMIN_PASSWORD),
+            ])
+            .send()
+            .await
+            .unwrap();
+        assert!(login.status().is_redirection());
+        // the session is renewed on login
+        let renewed = cookies(&login);
+        login_cookies.retain(|c| {
+            !renewed
+                .iter()
+                .any(|r| r.split('=').next() == c.split('=').next())
+        });
+        login_cookies.extend(renewed);
+        let cookie = login_cookies.join("; ");
+        assert!(signed_in(&client, web_url(&gitea, "/user/settings").await, &cookie).await);
+        gitea.rm().await.unwrap();
+
+        // another container with the same key and database accepts the session cookie
+        let gitea = start(&db_dir, "second-admin").await;
+        assert!(signed_in(&client, web_url(&gitea, "/user/settings").await, &cookie).await);
+        gitea.rm().await.unwrap();
+
+        // the database is owned by the user of the container, so this may fail
+        let _ = std::fs::remove_dir_all(&db_dir);
+    }
+
+    #[tokio::test]
+    async fn gitea_repo_ready_check() {
+        let gitea = Gitea::default()
+            .with_repo(GiteaRepo::Public(TEST_PUBLIC_REPO.to_string()))
+            .with_repo_ready_check()
+            .start()
+            .await
+            .unwrap();
+
+        let mut clone = gitea
+            .exec(
+                ExecCommand::new([
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    format!(
+                        "git clone -q http://localhost:{port}/{GITEA_DEFAULT_ADMIN_USERNAME}/{TEST_PUBLIC_REPO}.git /tmp/clone && git -C /tmp/clone log --format=%s -- README.md",
+                        port = GITEA_HTTP_PORT.as_u16()
+                    ),
+                ])
+                .with_cmd_ready_condition(CmdWaitFor::exit_code(0)),
+            )
+            .await
+            .unwrap();
+        let log = String::from_utf8(clone.stdout_to_vec().await.unwrap()).unwrap();
+        assert_eq!(log.trim(), "Initial commit");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn gitea_secret_key_shared_database() {
+        use std::os::unix::fs::PermissionsExt;
+
+        const SECRET_KEY: &str = "test-secret-key";
+        const AUTHORIZATION: &str = "Bearer test-webhook-token";
+
+        let db_dir = std::env::temp_dir().join(format!("gitea-secret-db-{}", std::process::id()));
+        std::fs::create_dir_all(&db_dir).unwrap();
+        // the server doesn't run as root
+        std::fs::set_permissions(&db_dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+        // the admin of the database exists already, so each container creates its own one
+        async fn start(db_dir: &Path, admin: &str, secret_key: &str) -> ContainerAsync<Gitea> {
+            Gitea::default()
+                .with_admin_account(admin, TEST_ADMIN_PASSWORD, None)
+                .with_secret_key(secret_key)
+                .with_internal_token("test-internal-token")
+                .with_sqlite_mount(db_dir)
+                .start()
+                .await
+                .unwrap()
+        }
+        async fn hooks(gitea: &ContainerAsync<Gitea>, admin: &str) -> reqwest::Response {
+            reqwest::Client::new()
+                .get(
+                    api_url(
+                        gitea,
+                        &format!("/repos/{TEST_ADMIN_USERNAME}/{TEST_PUBLIC_REPO}/hooks"),
+                    )
+                    .await,
+                )
+                .basic_auth(admin, Some(TEST_ADMIN_PASSWORD))
+                .send()
+                .await
+                .unwrap()
+        }
+
+        // the authorization header of a webhook is stored encrypted with the secret key
+        let gitea = Gitea::default()
+            .with_admin_account(TEST_ADMIN_USERNAME, TEST_ADMIN_PASSWORD, None)
+            .with_secret_key(SECRET_KEY)
+            .with_sqlite_mount(&db_dir)
+            .with_repo(GiteaRepo::Public(TEST_PUBLIC_REPO.to_string()))
+            .start()
+            .await
+            .unwrap();
+        let response = reqwest::Client::new()
+            .post(
+                api_url(
+                    &gitea,
+                    &format!("/repos/{TEST_ADMIN_USERNAME}/{TEST_PUBLIC_REPO}/hooks"),
+                )
+                .await,
+            )
+            .basic_auth(TEST_ADMIN_USERNAME, Some(TEST_ADMIN_PASSWORD))
+            .json(&serde_json::json!({
+                "type": "gitea",
+                "config": { "url": "http://example.com/hook", "content_type": "json" },
+                "events": ["push"],
+                "active": true,
+                "authorization_header": AUTHORIZATION,
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 201);
+        gitea.rm().await.unwrap();
+
+        // another container with the same key decrypts it
+        let gitea = start(&db_dir, "second-admin", SECRET_KEY).await;
+        let response = hooks(&gitea, "second-admin").await;
+        assert_eq!(response.status(), 200);
+        let hooks_list = response.json::<Value>().await.unwrap();
+        assert_eq!(hooks_list[0]["authorization_header"], AUTHORIZATION);
+        gitea.rm().await.unwrap();
+
+        // with another key it can't
+        let gitea = start(&db_dir, "third-admin", "another-secret-key").await;
+        let response = hooks(&gitea, "third-admin").await;
+        let decrypted = response.status().is_success()
+            && response.json::<Value>().await.unwrap()[0]["authorization_header"] == AUTHORIZATION;
+        assert!(!decrypted);
+        gitea.rm().await.unwrap();
+
+        // the database is owned by the user of the container, so this may fail
+        let _ = std::fs::remove_dir_all(&db_dir);
+    }
+
+    #[tokio::test]
+    async fn gitea_app_name_and_federation() {
+        let gitea = Gitea::default()
+            .with_app_name("Testcontainers Git")
+            .with_federation(true)
+            .start()
+            .await
+            .unwrap();
+
+        let api = api_url(&gitea, "").await;
+        let home = reqwest::get(api.trim_end_matches("api/v1/"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(home.contains("Testcontainers Git"));
+
+        let nodeinfo = reqwest::get(api_url(&gitea, "/nodeinfo").await)
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+        assert_eq!(nodeinfo["protocols"], serde_json::json!(["activitypub"]));
+    }
 }
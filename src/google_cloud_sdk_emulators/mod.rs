@@ -44,6 +44,25 @@ pub struct CloudSdkCmd {
     pub emulator: Emulator,
 }
 
+/// Mode of the database served by the Firestore emulator, see [`CloudSdk::with_database_mode`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FirestoreDatabaseMode {
+    /// Firestore in Native mode, the default of the emulator
+    Native,
+    /// Firestore in Datastore mode, serving the Datastore API
+    DatastoreMode,
+}
+
+impl FirestoreDatabaseMode {
+    /// Returns the value of `--database-mode`, e.g. `datastore-mode`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FirestoreDatabaseMode::Native => "firestore-native",
+            FirestoreDatabaseMode::DatastoreMode => "datastore-mode",
+        }
+    }
+}
+
 #[allow(missing_docs)]
 // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -92,6 +111,12 @@ pub struct CloudSdk {
     exposed_ports: Vec<ContainerPort>,
     ready_condition: WaitFor,
     cmd: CloudSdkCmd,
+    /// Project of the emulators other than [`Emulator::Datastore`], see [`CloudSdk::with_project`]
+    project: Option<String>,
+    /// Database mode of [`Emulator::Firestore`], see [`CloudSdk::with_database_mode`]
+    database_mode: Option<FirestoreDatabaseMode>,
+    /// Database of [`Emulator::Firestore`], see [`CloudSdk::with_database`]
+    database: Option<String>,
 }
 
 impl Image for CloudSdk {
@@ -108,7 +133,23 @@ impl Image for CloudSdk {
     }
 
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
-        &self.cmd
+        let mut args = self.cmd.into_iter().collect::<Vec<_>>();
+        // the Bigtable and Spanner emulators serve any project
+        if let (Emulator::Firestore | Emulator::PubSub, Some(project)) =
+            (&self.cmd.emulator, &self.project)
+        {
+            args.push("--project".to_owned());
+            args.push(project.clone());
+        }
+        if let Emulator::Firestore = self.cmd.emulator {
+            if let Some(mode) = self.database_mode {
+                args.push(format!("--database-mode={}", mode.as_str()));
+            }
+            if let Some(database) = &self.database {
+                args.push(format!("--database={database}"));
+            }
+        }
+        args
     }
 
     fn expose_ports(&self) -> &[ContainerPort] {
@@ -127,6 +168,52 @@ impl CloudSdk {
             exposed_ports: vec![ContainerPort::Tcp(port)],
             ready_condition,
             cmd,
+            project: None,
+            database_mode: None,
+            database: None,
+        }
+    }
+
+    /// Sets the Google Cloud project of the emulator, replacing the one of [`CloudSdk::datastore`].
+    ///
+    /// The project is passed as `--project` to the emulators which use it (Datastore, Firestore
+    /// and Pub/Sub). The Bigtable and Spanner emulators serve any project, so for them it's only
+    /// kept to be returned by [`CloudSdk::project`], e.g. to configure clients consistently.
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        let project = project.into();
+        match &mut self.cmd.emulator {
+            Emulator::Datastore { project: datastore } => *datastore = project,
+            _ => self.project = Some(project),
+        }
+        self
+    }
+
+    /// Sets the mode of the database served by the Firestore emulator (`--database-mode`),
+    /// e.g. [`FirestoreDatabaseMode::DatastoreMode`] to test against the Datastore API.
+    ///
+    /// Has no effect on the other emulators. The flag isn't known to the emulator of the default
+    /// image tag, so a more recent one has to be set via
+    /// [`ImageExt::with_tag`](testcontainers::ImageExt::with_tag).
+    pub fn with_database_mode(mut self, mode: FirestoreDatabaseMode) -> Self {
+        self.database_mode = Some(mode);
+        self
+    }
+
+    /// Sets the database served by the Firestore emulator (`--database`),
+    /// instead of the `(default)` one.
+    ///
+    /// Has no effect on the other emulators. Like [`CloudSdk::with_database_mode`], the flag
+    /// requires a more recent image tag than the default one.
+    pub fn with_database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Returns the project set via [`CloudSdk::with_project`] or [`CloudSdk::datastore`], if any.
+    pub fn project(&self) -> Option<&str> {
+        match &self.cmd.emulator {
+            Emulator::Datastore { project } => Some(project),
+            _ => self.project.as_deref(),
         }
     }
 
@@ -186,10 +273,112 @@ impl CloudSdk {
 mod tests {
     use std::ops::Range;
 
-    use crate::{google_cloud_sdk_emulators, testcontainers::runners::SyncRunner};
+    use crate::{
+        google_cloud_sdk_emulators::{self, CloudSdk, FirestoreDatabaseMode},
+        testcontainers::{runners::SyncRunner, Image},
+    };
 
     const RANDOM_PORTS: Range<u16> = 32768..65535;
 
+    fn args(sdk: &CloudSdk) -> Vec<String> {
+        sdk.cmd()
+            .into_iter()
+            .map(|arg| arg.into().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn with_project_command() {
+        let start = |emulator: &'static str| ["gcloud", "beta", "emulators", emulator, "start"];
+
+        let bigtable = CloudSdk::bigtable().with_project("test-project");
+        assert_eq!(
+            args(&bigtable),
+            [&start("bigtable")[..], &["--host-port", "0.0.0.0:8086"]].concat()
+        );
+        assert_eq!(bigtable.project(), Some("test-project"));
+
+        let datastore = CloudSdk::datastore("initial").with_project("test-project");
+        assert_eq!(
+            args(&datastore),
+            [
+                &start("datastore")[..],
+                &["--project", "test-project", "--host-port", "0.0.0.0:8081"]
+            ]
+            .concat()
+        );
+        assert_eq!(datastore.project(), Some("test-project"));
+
+        let firestore = CloudSdk::firestore().with_project("test-project");
+        assert_eq!(
+            args(&firestore),
+            [
+                &start("firestore")[..],
+                &["--host-port", "0.0.0.0:8080", "--project", "test-project"]
+            ]
+            .concat()
+        );
+        assert_eq!(firestore.project(), Some("test-project"));
+
+        let pubsub = CloudSdk::pubsub().with_project("test-project");
+        assert_eq!(
+            args(&pubsub),
+            [
+                &start("pubsub")[..],
+                &["--host-port", "0.0.0.0:8085", "--project", "test-project"]
+            ]
+            .concat()
+        );
+        assert_eq!(pubsub.project(), Some("test-project"));
+
+        let spanner = CloudSdk::spanner().with_project("test-project");
+        assert_eq!(
+            args(&spanner),
+            [&start("spanner")[..], &["--host-port", "0.0.0.0:9010"]].concat()
+        );
+        assert_eq!(spanner.project(), Some("test-project"));
+
+        assert_eq!(CloudSdk::pubsub().project(), None);
+    }
+
+    #[test]
+    fn firestore_datastore_mode_command() {
+        let firestore =
+            CloudSdk::firestore().with_database_mode(FirestoreDatabaseMode::DatastoreMode);
+        assert_eq!(
+            args(&firestore),
+            [
+                "gcloud",
+                "beta",
+                "emulators",
+                "firestore",
+                "start",
+                "--host-port",
+                "0.0.0.0:8080",
+                "--database-mode=datastore-mode",
+            ]
+        );
+
+        let firestore = CloudSdk::firestore()
+            .with_database_mode(FirestoreDatabaseMode::Native)
+            .with_database("test-db");
+        assert_eq!(
+            args(&firestore)[5..],
+            [
+                "--host-port",
+                "0.0.0.0:8080",
+                "--database-mode=firestore-native",
+                "--database=test-db",
+            ]
+        );
+
+        // only the Firestore emulator knows the flags
+        let datastore =
+            CloudSdk::datastore("test").with_database_mode(FirestoreDatabaseMode::DatastoreMode);
+        let datastore = datastore.with_database("test-db");
+        assert!(!args(&datastore).iter().any(|a| a.starts_with("--database")));
+    }
+
     #[test]
     fn bigtable_emulator_expose_port() -> Result<(), Box<dyn std::error::Error + 'static>> {
         let _ = pretty_env_logger::try_init();
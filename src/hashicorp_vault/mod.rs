@@ -1,9 +1,26 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display};
 
-use testcontainers::{core::WaitFor, Image};
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
+use testcontainers::{
+    core::{wait::HttpWaitStrategy, ContainerPort, WaitFor},
+    ContainerAsync, CopyDataSource, CopyToContainer, Image, TestcontainersError,
+};
+
+use crate::tls::{https_wait, GeneratedCert};
 
 const DEFAULT_IMAGE_NAME: &str = "hashicorp/vault";
 const DEFAULT_IMAGE_TAG: &str = "1.17";
+const DEV_ROOT_TOKEN_ID: &str = "VAULT_DEV_ROOT_TOKEN_ID";
+/// Port of the HTTP API of Vault inside of the container, used by [`address`]
+/// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
+pub const VAULT_PORT: ContainerPort = ContainerPort::Tcp(8200);
+const TLS_CERT_PATH: &str = "/vault/tls/cert.pem";
+const TLS_KEY_PATH: &str = "/vault/tls/key.pem";
+/// Extra config file with the HTTPS listener, loaded by the image's entrypoint in dev mode as well
+const TLS_LISTENER_CONFIG_PATH: &str = "/vault/config/tls_listener.hcl";
+/// The plaintext listener of the dev server is moved here, so it's unreachable from outside of the container
+const TLS_DEV_LISTEN_ADDRESS: &str = "127.0.0.1:8210";
 
 /// Module to work with [`Hashicorp Vault`] inside of tests.
 ///
@@ -11,12 +28,16 @@ const DEFAULT_IMAGE_TAG: &str = "1.17";
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
 /// use testcontainers_modules::{hashicorp_vault, testcontainers::runners::SyncRunner};
 ///
 /// let vault = hashicorp_vault::HashicorpVault::default().start().unwrap();
-/// let http_port = vault.get_host_port_ipv4(8200).unwrap();
+/// let address = hashicorp_vault::address_blocking(&vault).unwrap();
+/// let token = vault.image().token();
 ///
 /// // do something with the running vault instance..
+/// # }
 /// ```
 ///
 /// [`Hashicorp Vault`]: https://github.com/hashicorp/vault
@@ -27,6 +48,26 @@ pub struct HashicorpVault {
     name: String,
     tag: String,
     env_vars: BTreeMap<String, String>,
+    tls: Option<VaultTlsCert>,
+    copy_to_sources: Vec<CopyToContainer>,
+}
+
+/// PEM encoded certificate and key served by Vault, with the issuing CA if it was generated.
+#[derive(Debug, Clone)]
+struct VaultTlsCert {
+    cert: String,
+    key: String,
+    ca: Option<String>,
+}
+
+impl From<GeneratedCert> for VaultTlsCert {
+    fn from(GeneratedCert { cert, key, ca }: GeneratedCert) -> Self {
+        Self {
+            cert,
+            key,
+            ca: Some(ca),
+        }
+    }
 }
 
 impl Default for HashicorpVault {
@@ -36,7 +77,7 @@ impl Default for HashicorpVault {
      */
     fn default() -> Self {
         let mut env_vars = BTreeMap::new();
-        env_vars.insert("VAULT_DEV_ROOT_TOKEN_ID".to_string(), "myroot".to_string());
+        env_vars.insert(DEV_ROOT_TOKEN_ID.to_string(), "myroot".to_string());
         HashicorpVault::new(
             DEFAULT_IMAGE_NAME.to_string(),
             DEFAULT_IMAGE_TAG.to_string(),
@@ -51,10 +92,129 @@ impl HashicorpVault {
             name,
             tag,
             env_vars,
+            tls: None,
+            copy_to_sources: Vec::new(),
+        }
+    }
+
+    /// Sets the root token of the dev server (`VAULT_DEV_ROOT_TOKEN_ID`), `myroot` by default.
+    pub fn with_dev_root_token(mut self, token: impl Into<String>) -> Self {
+        self.env_vars
+            .insert(DEV_ROOT_TOKEN_ID.to_string(), token.into());
+        self
+    }
+
+    /// Returns the root token of the dev server, see [`HashicorpVault::with_dev_root_token`].
+    pub fn token(&self) -> &str {
+        &self.env_vars[DEV_ROOT_TOKEN_ID]
+    }
+
+    /// Serves the API on [`VAULT_PORT`] over HTTPS only, if `enabled`, using a generated self-signed certificate.
+    ///
+    /// The certificate is valid for `localhost`, `127.0.0.1` and `::1`, and its issuing CA
+    /// can be obtained with [`HashicorpVault::tls_ca`].
+    ///
+    /// The dev server still listens for plaintext HTTP, but only on the loopback interface
+    /// inside of the container.
+    pub fn with_tls(self, enabled: bool) -> Self {
+        self.with_tls_cert(enabled.then(|| GeneratedCert::new("Vault root CA", &[]).into()))
+    }
+
+    /// Serves the API on [`VAULT_PORT`] over HTTPS only, using the given certificate.
+    ///
+    /// `cert` and `key` are strings with PEM encoded certificate and its key.
+    /// This method is similar to [`HashicorpVault::with_tls`] but uses the provided certificate
+    /// instead of generating a self-signed one.
+    pub fn with_tls_certs(self, cert: impl Into<String>, key: impl Into<String>) -> Self {
+        self.with_tls_cert(Some(VaultTlsCert {
+            cert: cert.into(),
+            key: key.into(),
+            ca: None,
+        }))
+    }
+
+    fn with_tls_cert(mut self, tls: Option<VaultTlsCert>) -> Self {
+        match &tls {
+            Some(tls) => {
+                self.env_vars.insert(
+                    "VAULT_DEV_LISTEN_ADDRESS".to_string(),
+                    TLS_DEV_LISTEN_ADDRESS.to_string(),
+                );
+                let listener = format!(
+                    r#"listener "tcp" {{
+  address       = "0.0.0.0:{port}"
+  tls_cert_file = "{TLS_CERT_PATH}"
+  tls_key_file  = "{TLS_KEY_PATH}"
+}}
+"#,
+                    port = VAULT_PORT.as_u16()
+                );
+                self.copy_to_sources = vec![
+                    CopyToContainer::new(
+                        CopyDataSource::Data(tls.cert.clone().into_bytes()),
+                        TLS_CERT_PATH,
+                    ),
+                    CopyToContainer::new(
+                        CopyDataSource::Data(tls.key.clone().into_bytes()),
+                        TLS_KEY_PATH,
+                    ),
+                    CopyToContainer::new(
+                        CopyDataSource::Data(listener.into_bytes()),
+                        TLS_LISTENER_CONFIG_PATH,
+                    ),
+                ];
+            }
+            None => {
+                self.env_vars.remove("VAULT_DEV_LISTEN_ADDRESS");
+                self.copy_to_sources.clear();
+            }
         }
+        self.tls = tls;
+        self
+    }
+
+    /// Returns the PEM encoded CA certificate which issued the certificate of the Vault server,
+    /// if HTTPS is enabled via [`HashicorpVault::with_tls`].
+    ///
+    /// Returns `None` if HTTPS isn't enabled, or enabled with [`HashicorpVault::with_tls_certs`].
+    pub fn tls_ca(&self) -> Option<&str> {
+        self.tls.as_ref().and_then(|tls| tls.ca.as_deref())
+    }
+
+    /// Returns the address of the API, e.g. `http://127.0.0.1:8200`,
+    /// with `host_port` being the host port [`VAULT_PORT`] is mapped to.
+    /// The scheme is `https` if enabled via [`HashicorpVault::with_tls`] or [`HashicorpVault::with_tls_certs`].
+    ///
+    /// See [`address`] and [`address_blocking`] to get the address of a running container.
+    pub fn address(&self, host: impl Display, host_port: u16) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        format!("{scheme}://{host}:{host_port}")
     }
 }
 
+/// Returns the [`HashicorpVault::address`] of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`VAULT_PORT`].
+///
+/// Together with [`HashicorpVault::token`] this is all a client like `vaultrs` needs.
+pub async fn address(
+    container: &ContainerAsync<HashicorpVault>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(VAULT_PORT).await?;
+    Ok(container.image().address(host, port))
+}
+
+/// Returns the [`HashicorpVault::address`] of a container started with the `SyncRunner`,
+/// using the host port mapped to [`VAULT_PORT`].
+#[cfg(feature = "blocking")]
+pub fn address_blocking(
+    container: &Container<HashicorpVault>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(VAULT_PORT)?;
+    Ok(container.image().address(host, port))
+}
+
 impl Image for HashicorpVault {
     fn name(&self) -> &str {
         &self.name
@@ -65,7 +225,14 @@ impl Image for HashicorpVault {
     }
 
     fn ready_conditions(&self) -> Vec<WaitFor> {
-        vec![WaitFor::message_on_stdout("Vault server started!")]
+        // the dev server is initialized and unsealed once it's healthy
+        let health_check = match &self.tls {
+            Some(tls) => https_wait("/v1/sys/health", VAULT_PORT, tls.ca.as_deref()),
+            None => HttpWaitStrategy::new("/v1/sys/health").with_port(VAULT_PORT),
+        };
+        vec![WaitFor::http(
+            health_check.with_expected_status_code(200_u16),
+        )]
     }
 
     fn env_vars(
@@ -73,6 +240,14 @@ impl Image for HashicorpVault {
     ) -> impl IntoIterator<Item = (impl Into<Cow<'_, str>>, impl Into<Cow<'_, str>>)> {
         &self.env_vars
     }
+
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        &self.copy_to_sources
+    }
+
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &[VAULT_PORT]
+    }
 }
 
 #[cfg(test)]
@@ -96,14 +271,17 @@ mod tests {
     #[tokio::test]
     async fn hashicorp_vault_secret_set_and_read(
     ) -> Result<(), Box<dyn std::error::Error + 'static>> {
-        let vault = HashicorpVault::default().start().await.unwrap();
-        let endpoint = format!("http://0.0.0.0:{}", vault.get_host_port_ipv4(8200).await?);
+        let vault = HashicorpVault::default()
+            .with_dev_root_token("test-root-token")
+            .start()
+            .await
+            .unwrap();
 
         // Create a client
         let client = VaultClient::new(
             VaultClientSettingsBuilder::default()
-                .address(endpoint)
-                .token("myroot")
+                .address(address(&vault).await?)
+                .token(vault.image().token())
                 .build()
                 .unwrap(),
         )
@@ -120,4 +298,49 @@ mod tests {
         assert_eq!(secret.password, "secret");
         Ok(())
     }
+
+    #[tokio::test]
+    async fn hashicorp_vault_with_tls() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let vault = HashicorpVault::default().with_tls(true).start().await?;
+        let address = address(&vault).await?;
+        assert!(address.starts_with("https://"));
+        let health_url = format!("{address}/v1/sys/health");
+
+        // plaintext HTTP is rejected
+        let plaintext_url = health_url.replacen("https://", "http://", 1);
+        let response = reqwest::get(plaintext_url).await?;
+        assert_eq!(response.status(), 400);
+
+        // the certificate isn't trusted without the CA
+        assert!(reqwest::get(&health_url).await.is_err());
+
+        let ca = reqwest::Certificate::from_pem(vault.image().tls_ca().unwrap().as_bytes())?;
+        let client = reqwest::Client::builder()
+            .use_rustls_tls()
+            .add_root_certificate(ca)
+            .build()?;
+        let response = client.get(&health_url).send().await?;
+        assert_eq!(response.status(), 200);
+        Ok(())
+    }
+
+    #[test]
+    fn hashicorp_vault_tls_ca() {
+        assert!(HashicorpVault::default().tls_ca().is_none());
+        assert!(HashicorpVault::default().with_tls(true).tls_ca().is_some());
+        assert!(HashicorpVault::default()
+            .with_tls_certs("cert", "key")
+            .tls_ca()
+            .is_none());
+        let vault = HashicorpVault::default().with_tls(true).with_tls(false);
+        assert!(vault.tls_ca().is_none());
+        assert_eq!(vault.address("localhost", 8200), "http://localhost:8200");
+    }
+
+    #[test]
+    fn hashicorp_vault_dev_root_token() {
+        assert_eq!(HashicorpVault::default().token(), "myroot");
+        let vault = HashicorpVault::default().with_dev_root_token("test-root-token");
+        assert_eq!(vault.token(), "test-root-token");
+    }
 }
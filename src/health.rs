@@ -0,0 +1,50 @@
+use std::{future::Future, time::Duration};
+
+use testcontainers::{runners::AsyncRunner, ContainerAsync, Image, TestcontainersError};
+
+use crate::util::retry_until_success;
+
+/// Runs of the [`HealthProbe`] until [`ModuleHealthExt::start_healthy`] gives up, i.e. about a minute.
+const PROBE_ATTEMPTS: u32 = 600;
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Health probe of a module, checked by [`ModuleHealthExt::start_healthy`]
+/// in addition to the ready conditions of the image.
+pub trait HealthProbe: Image {
+    /// Returns the command probing the health of the module inside of the running container,
+    /// e.g. `curl` requesting an HTTP health endpoint, which succeeds once the module is healthy.
+    fn health_probe(&self) -> Vec<String>;
+}
+
+/// Starts containers of modules with a [`HealthProbe`] and waits until the probe passes.
+///
+/// Implemented for everything that can be started with the [`AsyncRunner`],
+/// i.e. the images themselves and their container requests.
+pub trait ModuleHealthExt<I: HealthProbe> {
+    /// Starts the container like [`AsyncRunner::start`], then runs the [`HealthProbe`] of the module
+    /// inside of the container until it succeeds, failing if it doesn't within about a minute.
+    fn start_healthy(
+        self,
+    ) -> impl Future<Output = Result<ContainerAsync<I>, TestcontainersError>> + Send;
+}
+
+impl<T, I> ModuleHealthExt<I> for T
+where
+    T: AsyncRunner<I> + Send,
+    I: HealthProbe,
+{
+    async fn start_healthy(self) -> Result<ContainerAsync<I>, TestcontainersError> {
+        let container = self.start().await?;
+        let probe = container.image().health_probe();
+        // the probe is passed as positional parameters, so it runs as is without quoting
+        container
+            .exec(retry_until_success(
+                r#""$@""#,
+                probe,
+                PROBE_ATTEMPTS,
+                PROBE_INTERVAL,
+            ))
+            .await?;
+        Ok(container)
+    }
+}
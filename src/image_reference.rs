@@ -0,0 +1,16 @@
+use testcontainers::Image;
+
+/// Exposes the image reference a module starts its container from,
+/// e.g. to audit the exact images used in locked-down CI environments.
+pub trait ImageReference: Image {
+    /// Returns the reference of the image as `name:tag`.
+    ///
+    /// This doesn't account for overrides via [`ImageExt::with_name`] or [`ImageExt::with_tag`],
+    /// which are applied to the container request and not to the image itself.
+    ///
+    /// [`ImageExt::with_name`]: testcontainers::ImageExt::with_name
+    /// [`ImageExt::with_tag`]: testcontainers::ImageExt::with_tag
+    fn image_reference(&self) -> String {
+        format!("{}:{}", self.name(), self.tag())
+    }
+}
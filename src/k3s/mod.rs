@@ -1,16 +1,20 @@
 use std::{
     borrow::Cow,
     collections::HashMap,
+    fmt::Display,
     io,
     io::ErrorKind,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use testcontainers::{
-    core::{ContainerPort, Mount, WaitFor},
-    Image,
+    core::{CmdWaitFor, ContainerPort, ContainerState, ExecCommand, Mount, WaitFor},
+    Image, TestcontainersError,
 };
 
+use crate::{util::retry_until_success, ModuleConfigError};
+
 const NAME: &str = "rancher/k3s";
 const TAG: &str = "v1.28.8-k3s1";
 /// Port that the [`traefik`] part of the container has internally
@@ -28,6 +32,19 @@ pub const KUBE_SECURE_PORT: ContainerPort = ContainerPort::Tcp(6443);
 ///
 /// [`Rancher`]: https://rancher.io/
 pub const RANCHER_WEBHOOK_PORT: ContainerPort = ContainerPort::Tcp(8443);
+/// Lists the pods, which succeeds once the first pods have been scheduled.
+const PODS_SCHEDULED_PROBE: &str = "kubectl get pods -A --no-headers 2>/dev/null | grep -q .";
+/// Waits up to 300 seconds until all pods, except completed ones like the helm install jobs, are Ready.
+const WAIT_FOR_READY_PODS: [&str; 8] = [
+    "kubectl",
+    "wait",
+    "--for=condition=Ready",
+    "pods",
+    "--all",
+    "-A",
+    "--field-selector=status.phase!=Succeeded",
+    "--timeout=300s",
+];
 
 /// Module to work with [`K3s`] inside of tests.
 ///
@@ -68,6 +85,7 @@ pub struct K3s {
     env_vars: HashMap<String, String>,
     conf_mount: Option<Mount>,
     cmd: K3sCmd,
+    ready_pods: bool,
 }
 
 #[allow(missing_docs)]
@@ -75,6 +93,9 @@ pub struct K3s {
 #[derive(Debug, Clone)]
 pub struct K3sCmd {
     snapshotter: String,
+    disabled: Vec<String>,
+    tls_sans: Vec<String>,
+    args: Vec<String>,
 }
 
 impl K3sCmd {
@@ -83,14 +104,42 @@ impl K3sCmd {
     pub fn with_snapshotter(self, snapshotter: impl Into<String>) -> Self {
         Self {
             snapshotter: snapshotter.into(),
+            ..self
         }
     }
+
+    /// Doesn't deploy the packaged `component` (`--disable`), e.g. `traefik` or `metrics-server`.
+    /// Can be called multiple times to disable several components.
+    ///
+    /// See the [`packaged components documentation`](https://docs.k3s.io/installation/packaged-components).
+    pub fn with_disable(mut self, component: impl Into<String>) -> Self {
+        self.disabled.push(component.into());
+        self
+    }
+
+    /// Adds `san` as subject alternative name to the certificate of the API server (`--tls-san`),
+    /// e.g. to connect to the cluster through a network alias. Can be called multiple times.
+    pub fn with_tls_san(mut self, san: impl Into<String>) -> Self {
+        self.tls_sans.push(san.into());
+        self
+    }
+
+    /// Appends `arg` to the arguments of `k3s server`, e.g. `--disable-network-policy`.
+    ///
+    /// See the [`server documentation`](https://docs.k3s.io/cli/server) for the available flags.
+    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
 }
 
 impl Default for K3sCmd {
     fn default() -> Self {
         Self {
             snapshotter: String::from("native"),
+            disabled: Vec::new(),
+            tls_sans: Vec::new(),
+            args: Vec::new(),
         }
     }
 }
@@ -131,6 +180,26 @@ impl Image for K3s {
     fn expose_ports(&self) -> &[ContainerPort] {
         &[KUBE_SECURE_PORT, RANCHER_WEBHOOK_PORT, TRAEFIK_HTTP]
     }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        if !self.ready_pods {
+            return Ok(vec![]);
+        }
+        // `kubectl wait` fails without any matching pods, so their scheduling is awaited first
+        Ok(vec![
+            retry_until_success(
+                PODS_SCHEDULED_PROBE,
+                Vec::<String>::new(),
+                300,
+                Duration::from_secs(1),
+            ),
+            ExecCommand::new(WAIT_FOR_READY_PODS)
+                .with_cmd_ready_condition(CmdWaitFor::exit_code(0)),
+        ])
+    }
 }
 
 impl K3s {
@@ -148,6 +217,29 @@ impl K3s {
         }
     }
 
+    /// Same as [`K3s::with_conf_mount`], but returns an error instead of mounting an empty path
+    /// when `conf_mount_path` isn't valid UTF-8.
+    pub fn try_with_conf_mount(
+        self,
+        conf_mount_path: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let conf_mount_path = ModuleConfigError::utf8_path(conf_mount_path.as_ref())?;
+        Ok(self.with_conf_mount(conf_mount_path))
+    }
+
+    /// Waits on start until the system pods (`coredns`, `metrics-server`, `local-path-provisioner`, ...)
+    /// are Ready, using `kubectl wait` inside the container.
+    ///
+    /// By default the container is considered ready as soon as the node is synced, when the system pods
+    /// may still be starting. Waiting for them usually adds 10 to 30 seconds to the startup,
+    /// depending on the host and whether the images of the pods have to be pulled.
+    pub fn with_ready_pods(self) -> Self {
+        Self {
+            ready_pods: true,
+            ..self
+        }
+    }
+
     // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
     #[allow(missing_docs)]
     pub fn read_kube_config(&self) -> io::Result<String> {
@@ -161,6 +253,22 @@ impl K3s {
 
         std::fs::read_to_string(k3s_conf_file_path)
     }
+
+    /// Reads the kube config like [`K3s::read_kube_config`], but points its API server
+    /// address to `<host>:<kube_port>`, i.e. the host of the container and the port
+    /// [`KUBE_SECURE_PORT`] is mapped to on it.
+    ///
+    /// The returned config can be used by clients outside of the container as is.
+    /// This doesn't require an async runtime, so it's usable with both runners.
+    /// The certificate of the API server is only valid for local addresses by default,
+    /// so add a remote host with [`K3sCmd::with_tls_san`].
+    pub fn read_kube_config_for(&self, host: impl Display, kube_port: u16) -> io::Result<String> {
+        let conf_yaml = self.read_kube_config()?;
+        Ok(conf_yaml.replace(
+            &format!("https://127.0.0.1:{}", KUBE_SECURE_PORT.as_u16()),
+            &format!("https://{host}:{kube_port}"),
+        ))
+    }
 }
 
 impl IntoIterator for &K3sCmd {
@@ -170,6 +278,9 @@ impl IntoIterator for &K3sCmd {
     fn into_iter(self) -> Self::IntoIter {
         let mut cmd = vec![String::from("server")];
         cmd.push(format!("--snapshotter={}", self.snapshotter));
+        cmd.extend(self.disabled.iter().map(|c| format!("--disable={c}")));
+        cmd.extend(self.tls_sans.iter().map(|san| format!("--tls-san={san}")));
+        cmd.extend(self.args.iter().cloned());
         cmd.into_iter()
     }
 }
@@ -232,6 +343,129 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn k3s_pods_without_traefik() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let conf_dir = temp_dir();
+        let k3s = K3s::default()
+            .with_conf_mount(&conf_dir)
+            .with_privileged(true)
+            .with_userns_mode("host")
+            .with_cmd(&K3sCmd::default().with_disable("traefik"));
+
+        let k3s_container = k3s.start().await?;
+
+        let client = get_kube_client(&k3s_container).await?;
+
+        let pod_names = Api::<Pod>::all(client)
+            .list(&ListParams::default())
+            .await
+            .expect("Cannot read pods")
+            .into_iter()
+            .map(|pod| pod.name_any())
+            .collect::<Vec<_>>();
+
+        assert!(
+            pod_names
+                .iter()
+                .any(|pod_name| pod_name.starts_with("coredns")),
+            "coredns pod not found - found pods {pod_names:?}"
+        );
+        assert!(
+            !pod_names
+                .iter()
+                .any(|pod_name| pod_name.contains("traefik")),
+            "traefik should be disabled - found pods {pod_names:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn k3s_ready_pods() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let conf_dir = temp_dir();
+        let k3s_container = K3s::default()
+            .with_conf_mount(&conf_dir)
+            .with_ready_pods()
+            .with_privileged(true)
+            .with_userns_mode("host")
+            .start()
+            .await?;
+
+        let client = get_kube_client(&k3s_container).await?;
+
+        let pods = Api::<Pod>::namespaced(client, "kube-system")
+            .list(&ListParams::default())
+            .await
+            .expect("Cannot read pods");
+        for name in ["coredns", "metrics-server", "local-path-provisioner"] {
+            let pod = pods
+                .iter()
+                .find(|pod| pod.name_any().starts_with(name))
+                .unwrap_or_else(|| panic!("{name} pod not found"));
+            let phase = pod.status.as_ref().and_then(|s| s.phase.as_deref());
+            assert_eq!(phase, Some("Running"), "{name} pod isn't running");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn k3s_cmd_args() {
+        let cmd = K3sCmd::default()
+            .with_arg("--disable-network-policy")
+            .with_disable("traefik")
+            .with_tls_san("k3s.local")
+            .with_disable("metrics-server")
+            .with_snapshotter("fuse-overlayfs");
+        assert_eq!(
+            (&cmd).into_iter().collect::<Vec<_>>(),
+            vec![
+                "server",
+                "--snapshotter=fuse-overlayfs",
+                "--disable=traefik",
+                "--disable=metrics-server",
+                "--tls-san=k3s.local",
+                "--disable-network-policy",
+            ]
+        );
+    }
+
+    #[test]
+    fn k3s_read_kube_config_for_sync() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        use testcontainers::runners::SyncRunner;
+
+        let conf_dir = temp_dir();
+        let k3s_container = SyncRunner::start(
+            K3s::default()
+                .with_conf_mount(&conf_dir)
+                .with_privileged(true)
+                .with_userns_mode("host"),
+        )?;
+
+        let host = k3s_container.get_host()?;
+        let port = k3s_container.get_host_port_ipv4(KUBE_SECURE_PORT)?;
+        let conf_yaml = k3s_container.image().read_kube_config_for(&host, port)?;
+
+        let config = Kubeconfig::from_yaml(&conf_yaml)?;
+        let server = config.clusters[0]
+            .cluster
+            .as_ref()
+            .and_then(|c| c.server.clone());
+        assert_eq!(server, Some(format!("https://{host}:{port}")));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn k3s_conf_mount_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/k3s-\xff"));
+        let result = K3s::default().try_with_conf_mount(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
     pub async fn get_kube_client(
         container: &ContainerAsync<K3s>,
     ) -> Result<kube::Client, Box<dyn std::error::Error + 'static>> {
@@ -241,16 +475,11 @@ mod tests {
                 .expect("Error initializing rustls provider");
         }
 
-        let conf_yaml = container.image().read_kube_config()?;
-
-        let mut config = Kubeconfig::from_yaml(&conf_yaml).expect("Error loading kube config");
-
+        let host = container.get_host().await?;
         let port = container.get_host_port_ipv4(KUBE_SECURE_PORT).await?;
-        config.clusters.iter_mut().for_each(|cluster| {
-            if let Some(server) = cluster.cluster.as_mut().and_then(|c| c.server.as_mut()) {
-                *server = format!("https://127.0.0.1:{}", port)
-            }
-        });
+        let conf_yaml = container.image().read_kube_config_for(host, port)?;
+
+        let config = Kubeconfig::from_yaml(&conf_yaml).expect("Error loading kube config");
 
         let client_config =
             Config::from_custom_kubeconfig(config, &KubeConfigOptions::default()).await?;
@@ -1,8 +1,10 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, fmt::Display};
 
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
-    core::{ContainerPort, ContainerState, ExecCommand, WaitFor},
-    Image,
+    core::{CmdWaitFor, ContainerPort, ContainerState, ExecCommand, WaitFor},
+    ContainerAsync, Image, TestcontainersError,
 };
 
 const KAFKA_NATIVE_IMAGE_NAME: &str = "apache/kafka-native";
@@ -19,6 +21,7 @@ const START_SCRIPT: &str = "/opt/kafka/testcontainers_start.sh";
 const DEFAULT_INTERNAL_TOPIC_RF: usize = 1;
 const DEFAULT_CLUSTER_ID: &str = "5L6g3nShT-eMCtK--X86sw";
 const DEFAULT_BROKER_ID: usize = 1;
+const ACL_AUTHORIZER: &str = "org.apache.kafka.metadata.authorizer.StandardAuthorizer";
 
 /// Module to work with [`Apache Kafka`] broker
 ///
@@ -34,9 +37,13 @@ const DEFAULT_BROKER_ID: usize = 1;
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
 /// use testcontainers_modules::{kafka::apache, testcontainers::runners::SyncRunner};
 /// let kafka_node = apache::Kafka::default().start().unwrap();
+/// let bootstrap_servers = apache::bootstrap_servers_blocking(&kafka_node).unwrap();
 /// // connect to kafka server to send/receive messages
+/// # }
 /// ```
 ///
 /// [`Apache Kafka`]: https://kafka.apache.org/
@@ -44,6 +51,25 @@ const DEFAULT_BROKER_ID: usize = 1;
 pub struct Kafka {
     env_vars: HashMap<String, String>,
     image_name: String,
+    acls: Vec<Vec<String>>,
+}
+
+/// Resource an ACL created by [`Kafka::with_acl`] or [`Kafka::with_deny_acl`] applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AclResource {
+    /// Topic with the given name.
+    Topic(String),
+    /// Consumer group with the given id.
+    Group(String),
+}
+
+impl AclResource {
+    fn args(&self) -> Vec<String> {
+        match self {
+            AclResource::Topic(name) => vec!["--topic".to_string(), name.clone()],
+            AclResource::Group(id) => vec!["--group".to_string(), id.clone()],
+        }
+    }
 }
 
 impl Default for Kafka {
@@ -94,6 +120,7 @@ impl Default for Kafka {
         Self {
             env_vars,
             image_name: KAFKA_NATIVE_IMAGE_NAME.to_string(),
+            acls: vec![],
         }
     }
 }
@@ -105,6 +132,123 @@ impl Kafka {
 
         self
     }
+
+    /// Allows `principal` (e.g. `User:alice`) to perform `operation` (e.g. `Write`) on `resource`.
+    ///
+    /// The ACL is created with `kafka-acls.sh` once the broker is started, which is only shipped
+    /// with the JVM image, so this switches to it like [`Kafka::with_jvm_image`].
+    ///
+    /// Adding an ACL enables the `StandardAuthorizer`. Resources without any ACL stay accessible
+    /// to everyone, so the broker keeps working for all other clients.
+    /// Note that all clients connecting through the plaintext [`KAFKA_PORT`] are `User:ANONYMOUS`.
+    ///
+    /// Rules on the cluster itself aren't supported: the broker connects to itself as `User:ANONYMOUS`
+    /// too, so they would apply to it, and to the creation of the later ACLs.
+    pub fn with_acl(
+        self,
+        principal: impl Into<String>,
+        operation: impl Into<String>,
+        resource: AclResource,
+    ) -> Self {
+        self.with_acl_rule(
+            "--allow-principal",
+            principal.into(),
+            operation.into(),
+            resource,
+        )
+    }
+
+    /// Denies `principal` to perform `operation` on `resource`.
+    ///
+    /// See [`Kafka::with_acl`] for the requirements.
+    pub fn with_deny_acl(
+        self,
+        principal: impl Into<String>,
+        operation: impl Into<String>,
+        resource: AclResource,
+    ) -> Self {
+        self.with_acl_rule(
+            "--deny-principal",
+            principal.into(),
+            operation.into(),
+            resource,
+        )
+    }
+
+    fn with_acl_rule(
+        mut self,
+        permission: &str,
+        principal: String,
+        operation: String,
+        resource: AclResource,
+    ) -> Self {
+        self = self.with_jvm_image();
+        self.env_vars.insert(
+            "KAFKA_AUTHORIZER_CLASS_NAME".to_owned(),
+            ACL_AUTHORIZER.to_owned(),
+        );
+        self.env_vars.insert(
+            "KAFKA_ALLOW_EVERYONE_IF_NO_ACL_FOUND".to_owned(),
+            "true".to_owned(),
+        );
+
+        let mut cmd = vec![
+            "/opt/kafka/bin/kafka-acls.sh".to_string(),
+            "--bootstrap-server".to_string(),
+            "localhost:9093".to_string(),
+            "--add".to_string(),
+            permission.to_string(),
+            principal,
+            "--operation".to_string(),
+            operation,
+        ];
+        cmd.extend(resource.args());
+        self.acls.push(cmd);
+
+        self
+    }
+
+    /// Returns the `bootstrap.servers` for clients, with `host_port` being
+    /// the host port [`KAFKA_PORT`] is mapped to.
+    ///
+    /// See [`bootstrap_servers`] and [`bootstrap_servers_blocking`] to get them for a running container.
+    ///
+    /// Pass a `host` matching the [advertised host](super#advertised-host),
+    /// e.g. the one returned by `get_host`.
+    pub fn bootstrap_servers(&self, host: impl Display, host_port: u16) -> String {
+        super::bootstrap_servers_of(host, host_port)
+    }
+
+    /// Returns the client properties to connect to the broker, with `host_port` being
+    /// the host port [`KAFKA_PORT`] is mapped to.
+    ///
+    /// Contains the [`Kafka::bootstrap_servers`] and `security.protocol`, which is always
+    /// `PLAINTEXT` on [`KAFKA_PORT`]. The map can be passed to any client as is,
+    /// and extended with client specific properties.
+    pub fn client_config(&self, host: impl Display, host_port: u16) -> HashMap<String, String> {
+        super::client_config_of(host, host_port)
+    }
+}
+
+/// Returns the `bootstrap.servers` of a container started with the `AsyncRunner`,
+/// e.g. `localhost:32768`, using the host port mapped to [`KAFKA_PORT`].
+pub async fn bootstrap_servers(
+    container: &ContainerAsync<Kafka>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(KAFKA_PORT).await?;
+    Ok(container.image().bootstrap_servers(host, port))
+}
+
+/// Returns the `bootstrap.servers` of a container started with the `SyncRunner`,
+/// e.g. `localhost:32768`, using the host port mapped to [`KAFKA_PORT`].
+#[cfg(feature = "blocking")]
+pub fn bootstrap_servers_blocking(
+    container: &Container<Kafka>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(KAFKA_PORT)?;
+    Ok(container.image().bootstrap_servers(host, port))
 }
 
 impl Image for Kafka {
@@ -166,7 +310,8 @@ impl Image for Kafka {
             "sh".to_string(),
             "-c".to_string(),
             format!(
-                "echo '#!/usr/bin/env bash\nexport KAFKA_ADVERTISED_LISTENERS=PLAINTEXT://127.0.0.1:{},BROKER://localhost:9093\n/etc/kafka/docker/run \n' > {}",
+                "echo '#!/usr/bin/env bash\nexport KAFKA_ADVERTISED_LISTENERS=PLAINTEXT://{}:{},BROKER://localhost:9093\n/etc/kafka/docker/run \n' > {}",
+                super::advertised_host(),
                 cs.host_port_ipv4(KAFKA_PORT)?,
                 START_SCRIPT
             ),
@@ -176,13 +321,20 @@ impl Image for Kafka {
         // for the message in container log, not script output.
         commands.push(ExecCommand::new(cmd).with_container_ready_conditions(ready_conditions));
 
+        // ACLs can only be created once the broker is up
+        commands.extend(
+            self.acls.iter().map(|acl| {
+                ExecCommand::new(acl).with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+            }),
+        );
+
         Ok(commands)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{collections::HashMap, time::Duration};
 
     use futures::StreamExt;
     use rdkafka::{
@@ -190,20 +342,39 @@ mod tests {
         producer::{FutureProducer, FutureRecord},
         ClientConfig, Message,
     };
-    use testcontainers::runners::AsyncRunner;
+    use testcontainers::{runners::AsyncRunner, Image};
 
     use crate::kafka::apache;
 
+    #[test]
+    fn acl_switches_to_jvm_image() {
+        let kafka = apache::Kafka::default().with_acl(
+            "User:ANONYMOUS",
+            "Read",
+            apache::AclResource::Topic("test-topic".to_string()),
+        );
+        assert_eq!(kafka.name(), apache::KAFKA_IMAGE_NAME);
+    }
+
+    #[test]
+    fn kafka_client_config() {
+        let config = apache::Kafka::default().client_config("127.0.0.1", 9092);
+        assert_eq!(
+            config,
+            HashMap::from([
+                ("bootstrap.servers".to_owned(), "127.0.0.1:9092".to_owned()),
+                ("security.protocol".to_owned(), "PLAINTEXT".to_owned()),
+            ])
+        );
+    }
+
     #[tokio::test]
     async fn produce_and_consume_messages_graalvm(
     ) -> Result<(), Box<dyn std::error::Error + 'static>> {
         let _ = pretty_env_logger::try_init();
         let kafka_node = apache::Kafka::default().start().await?;
 
-        let bootstrap_servers = format!(
-            "127.0.0.1:{}",
-            kafka_node.get_host_port_ipv4(apache::KAFKA_PORT).await?
-        );
+        let bootstrap_servers = apache::bootstrap_servers(&kafka_node).await?;
 
         let producer = ClientConfig::new()
             .set("bootstrap.servers", &bootstrap_servers)
@@ -270,10 +441,7 @@ mod tests {
         let _ = pretty_env_logger::try_init();
         let kafka_node = apache::Kafka::default().with_jvm_image().start().await?;
 
-        let bootstrap_servers = format!(
-            "127.0.0.1:{}",
-            kafka_node.get_host_port_ipv4(apache::KAFKA_PORT).await?
-        );
+        let bootstrap_servers = apache::bootstrap_servers(&kafka_node).await?;
 
         let producer = ClientConfig::new()
             .set("bootstrap.servers", &bootstrap_servers)
@@ -333,4 +501,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn produce_with_deny_acl() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let kafka_node = apache::Kafka::default()
+            .with_deny_acl(
+                "User:ANONYMOUS",
+                "Write",
+                apache::AclResource::Topic("denied-topic".to_string()),
+            )
+            .start()
+            .await?;
+
+        let bootstrap_servers = apache::bootstrap_servers(&kafka_node).await?;
+
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &bootstrap_servers)
+            .set("message.timeout.ms", "5000")
+            .create::<FutureProducer>()
+            .expect("Failed to create Kafka FutureProducer");
+
+        let denied = producer
+            .send(
+                FutureRecord::to("denied-topic")
+                    .payload("denied")
+                    .key("key"),
+                Duration::from_secs(0),
+            )
+            .await;
+        assert!(denied.is_err(), "write to denied topic should be rejected");
+
+        let allowed = producer
+            .send(
+                FutureRecord::to("allowed-topic")
+                    .payload("allowed")
+                    .key("key"),
+                Duration::from_secs(0),
+            )
+            .await;
+        assert!(allowed.is_ok(), "write to topic without ACL should succeed");
+
+        Ok(())
+    }
 }
@@ -1,10 +1,14 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, fmt::Display, path::Path};
 
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
-    core::{ContainerPort, ContainerState, ExecCommand, WaitFor},
-    Image,
+    core::{ContainerPort, ContainerState, ExecCommand, Mount, WaitFor},
+    ContainerAsync, Image, TestcontainersError,
 };
 
+use crate::{ImageReference, ModuleConfigError};
+
 const NAME: &str = "confluentinc/cp-kafka";
 const TAG: &str = "6.1.1";
 /// Port that the [`Kafka`] part of the container has internally
@@ -17,12 +21,47 @@ pub const KAFKA_PORT: ContainerPort = ContainerPort::Tcp(9093);
 ///
 /// [`Zookeeper`]: https://zookeeper.apache.org/
 pub const ZOOKEEPER_PORT: ContainerPort = ContainerPort::Tcp(2181);
+/// Directory inside of the container where the broker stores its log segments (`log.dirs`),
+/// see [`Kafka::with_log_dir`]
+pub const KAFKA_LOG_DIR: &str = "/var/lib/kafka/data";
 
-#[allow(missing_docs)]
-// not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
+/// Module to work with [`Kafka`] based on the [`Confluent Kafka docker image`] inside of tests.
+///
+/// The broker is available on [`KAFKA_PORT`]. Like any image, the container can be given a fixed name
+/// with [`ImageExt::with_container_name`], e.g. to correlate its logs with the test.
+///
+/// [`Kafka`]: https://kafka.apache.org/
+/// [`Confluent Kafka docker image`]: https://hub.docker.com/r/confluentinc/cp-kafka
+/// [`ImageExt::with_container_name`]: testcontainers::ImageExt::with_container_name
 #[derive(Debug, Clone)]
 pub struct Kafka {
     env_vars: HashMap<String, String>,
+    log_dir_mount: Option<Mount>,
+}
+
+/// Compression codecs of Kafka, see [`Kafka::with_compression_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// `gzip` compression
+    Gzip,
+    /// `snappy` compression
+    Snappy,
+    /// `lz4` compression
+    Lz4,
+    /// `zstd` compression
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the name Kafka uses for the codec, e.g. `gzip`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Snappy => "snappy",
+            Compression::Lz4 => "lz4",
+            Compression::Zstd => "zstd",
+        }
+    }
 }
 
 impl Default for Kafka {
@@ -61,8 +100,138 @@ impl Default for Kafka {
             "1".to_owned(),
         );
 
-        Self { env_vars }
+        Self {
+            env_vars,
+            log_dir_mount: None,
+        }
+    }
+}
+
+impl Kafka {
+    /// Enables JMX on the broker at the given `port`, reachable from inside of the container only,
+    /// so broker metrics can be scraped during tests.
+    ///
+    /// Connect to it from commands run in the container, e.g. with
+    /// `kafka-run-class kafka.tools.JmxTool --jmx-url service:jmx:rmi:///jndi/rmi://localhost:<port>/jmxrmi`
+    /// run via [`ContainerAsync::exec`]. The RMI stub the broker hands out points to `localhost`
+    /// and the container port, so clients on the host can't follow it through a mapped port,
+    /// which is why the port isn't exposed.
+    ///
+    /// Only `KAFKA_JMX_PORT` is set, which the image's launch script exports as `JMX_PORT`
+    /// for the broker process. Setting `JMX_PORT` for the whole container would make
+    /// the Kafka CLI tools executed inside of it try to bind the same port.
+    pub fn with_internal_jmx(mut self, port: u16) -> Self {
+        self.env_vars
+            .insert("KAFKA_JMX_PORT".to_owned(), port.to_string());
+        self.env_vars
+            .insert("KAFKA_JMX_HOSTNAME".to_owned(), "localhost".to_owned());
+        self
+    }
+
+    /// Sets the largest record batch size the broker accepts (`message.max.bytes`),
+    /// and `replica.fetch.max.bytes` to match, so large-payload tests work.
+    ///
+    /// Note that clients have limits on their own, e.g. `message.max.bytes` of the producer.
+    pub fn with_message_max_bytes(mut self, max_bytes: u32) -> Self {
+        self.env_vars
+            .insert("KAFKA_MESSAGE_MAX_BYTES".to_owned(), max_bytes.to_string());
+        self.env_vars.insert(
+            "KAFKA_REPLICA_FETCH_MAX_BYTES".to_owned(),
+            max_bytes.to_string(),
+        );
+        self
+    }
+
+    /// Sets the compression the broker applies to stored messages (`compression.type`),
+    /// regardless of the compression used by the producers.
+    ///
+    /// Consumers have to support the codec to read the messages.
+    pub fn with_compression_type(mut self, compression: Compression) -> Self {
+        self.env_vars.insert(
+            "KAFKA_COMPRESSION_TYPE".to_owned(),
+            compression.as_str().to_owned(),
+        );
+        self
+    }
+
+    /// Sets the number of partitions of topics created automatically on first use (`num.partitions`),
+    /// 1 by default.
+    ///
+    /// Topics created explicitly, e.g. via an admin client, use the partition count given there.
+    pub fn with_default_partitions(mut self, partitions: u32) -> Self {
+        self.env_vars
+            .insert("KAFKA_NUM_PARTITIONS".to_owned(), partitions.to_string());
+        self
+    }
+
+    /// Stores the log segments of the broker in `host_dir`, bind-mounted to [`KAFKA_LOG_DIR`]
+    /// (`log.dirs`), so tests can inspect the segment files, e.g. `<topic>-<partition>/*.log`.
+    ///
+    /// The broker doesn't run as root, so `host_dir` has to be writable by other users.
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [`Kafka::try_with_log_dir`].
+    pub fn with_log_dir(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_log_dir(host_dir)
+            .expect("log directory is not valid UTF-8")
     }
+
+    /// Same as [`Kafka::with_log_dir`], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_log_dir(
+        mut self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        self.env_vars
+            .insert("KAFKA_LOG_DIRS".to_owned(), KAFKA_LOG_DIR.to_owned());
+        self.log_dir_mount = Some(Mount::bind_mount(host_dir, KAFKA_LOG_DIR));
+        Ok(self)
+    }
+
+    /// Returns the `bootstrap.servers` for clients, with `host_port` being
+    /// the host port [`KAFKA_PORT`] is mapped to.
+    ///
+    /// See [`bootstrap_servers`] and [`bootstrap_servers_blocking`] to get them for a running container.
+    ///
+    /// Pass a `host` matching the [advertised host](super#advertised-host),
+    /// e.g. the one returned by `get_host`.
+    pub fn bootstrap_servers(&self, host: impl Display, host_port: u16) -> String {
+        super::bootstrap_servers_of(host, host_port)
+    }
+
+    /// Returns the client properties to connect to the broker, with `host_port` being
+    /// the host port [`KAFKA_PORT`] is mapped to.
+    ///
+    /// Contains the [`Kafka::bootstrap_servers`] and `security.protocol`, which is always `PLAINTEXT`
+    /// since the broker doesn't support authentication or TLS. The map can be passed to
+    /// any client as is, e.g. collected into an `rdkafka::ClientConfig` for an admin client,
+    /// and extended with client specific properties.
+    pub fn client_config(&self, host: impl Display, host_port: u16) -> HashMap<String, String> {
+        super::client_config_of(host, host_port)
+    }
+}
+
+/// Returns the [`Kafka::bootstrap_servers`] of a container started with the `AsyncRunner`,
+/// e.g. `localhost:32768`, using the host port mapped to [`KAFKA_PORT`].
+pub async fn bootstrap_servers(
+    container: &ContainerAsync<Kafka>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(KAFKA_PORT).await?;
+    Ok(container.image().bootstrap_servers(host, port))
+}
+
+/// Returns the [`Kafka::bootstrap_servers`] of a container started with the `SyncRunner`,
+/// e.g. `localhost:32768`, using the host port mapped to [`KAFKA_PORT`].
+#[cfg(feature = "blocking")]
+pub fn bootstrap_servers_blocking(
+    container: &Container<Kafka>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(KAFKA_PORT)?;
+    Ok(container.image().bootstrap_servers(host, port))
 }
 
 impl Image for Kafka {
@@ -84,6 +253,10 @@ impl Image for Kafka {
         &self.env_vars
     }
 
+    fn mounts(&self) -> impl IntoIterator<Item = &Mount> {
+        self.log_dir_mount.as_ref()
+    }
+
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
         vec![
             "/bin/bash".to_owned(),
@@ -122,7 +295,8 @@ zookeeper-server-start zookeeper.properties &
             "1".to_string(),
             "--add-config".to_string(),
             format!(
-                "advertised.listeners=[PLAINTEXT://127.0.0.1:{},BROKER://localhost:9092]",
+                "advertised.listeners=[PLAINTEXT://{}:{},BROKER://localhost:9092]",
+                super::advertised_host(),
                 cs.host_port_ipv4(KAFKA_PORT)?
             ),
         ];
@@ -134,29 +308,68 @@ zookeeper-server-start zookeeper.properties &
     }
 }
 
+impl ImageReference for Kafka {}
+
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{collections::HashMap, time::Duration};
 
     use futures::StreamExt;
     use rdkafka::{
+        admin::{AdminClient, AdminOptions, ResourceSpecifier},
+        client::DefaultClientContext,
         consumer::{Consumer, StreamConsumer},
-        producer::{FutureProducer, FutureRecord},
+        error::KafkaError,
+        producer::{FutureProducer, FutureRecord, Producer},
+        types::RDKafkaErrorCode,
         ClientConfig, Message,
     };
-    use testcontainers::runners::AsyncRunner;
+    use testcontainers::{core::ExecCommand, runners::AsyncRunner, Image};
+
+    use crate::{kafka, ImageReference};
+
+    #[test]
+    fn kafka_image_reference() {
+        let kafka = kafka::Kafka::default();
+        assert_eq!(
+            kafka.image_reference(),
+            format!("{}:{}", kafka.name(), kafka.tag())
+        );
+    }
+
+    #[test]
+    fn kafka_client_config() {
+        let config = kafka::Kafka::default().client_config("127.0.0.1", 9093);
+        assert_eq!(
+            config,
+            HashMap::from([
+                ("bootstrap.servers".to_owned(), "127.0.0.1:9093".to_owned()),
+                ("security.protocol".to_owned(), "PLAINTEXT".to_owned()),
+            ])
+        );
+    }
 
-    use crate::kafka;
+    #[cfg(unix)]
+    #[test]
+    fn kafka_log_dir_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::Path};
+
+        use crate::ModuleConfigError;
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/kafka-\xff"));
+        let result = kafka::Kafka::default().try_with_log_dir(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
 
     #[tokio::test]
     async fn produce_and_consume_messages() -> Result<(), Box<dyn std::error::Error + 'static>> {
         let _ = pretty_env_logger::try_init();
         let kafka_node = kafka::Kafka::default().start().await?;
 
-        let bootstrap_servers = format!(
-            "127.0.0.1:{}",
-            kafka_node.get_host_port_ipv4(kafka::KAFKA_PORT).await?
-        );
+        let bootstrap_servers = kafka::bootstrap_servers(&kafka_node).await?;
 
         let producer = ClientConfig::new()
             .set("bootstrap.servers", &bootstrap_servers)
@@ -216,4 +429,219 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn broker_metrics_via_jmx() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let jmx_port = 9999;
+        let kafka_node = kafka::Kafka::default()
+            .with_internal_jmx(jmx_port)
+            .start()
+            .await?;
+
+        let mut result = kafka_node
+            .exec(ExecCommand::new([
+                "kafka-run-class".to_string(),
+                "kafka.tools.JmxTool".to_string(),
+                "--object-name".to_string(),
+                "kafka.server:type=BrokerTopicMetrics,name=MessagesInPerSec".to_string(),
+                "--jmx-url".to_string(),
+                format!("service:jmx:rmi:///jndi/rmi://localhost:{jmx_port}/jmxrmi"),
+                "--one-time".to_string(),
+                "true".to_string(),
+            ]))
+            .await?;
+        let stdout = String::from_utf8(result.stdout_to_vec().await?)?;
+
+        assert!(
+            stdout.contains("kafka.server:type=BrokerTopicMetrics,name=MessagesInPerSec:Count"),
+            "unexpected JmxTool output: {stdout}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn produce_with_compression_type() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let kafka_node = kafka::Kafka::default()
+            .with_compression_type(kafka::Compression::Gzip)
+            .start()
+            .await?;
+
+        let client_config = kafka_node.image().client_config(
+            kafka_node.get_host().await?,
+            kafka_node.get_host_port_ipv4(kafka::KAFKA_PORT).await?,
+        );
+        let bootstrap_servers = client_config["bootstrap.servers"].clone();
+
+        let admin = ClientConfig::from_iter(client_config)
+            .create::<AdminClient<DefaultClientContext>>()
+            .expect("Failed to create Kafka AdminClient");
+        let configs = admin
+            .describe_configs([&ResourceSpecifier::Broker(1)], &AdminOptions::new())
+            .await?;
+        let broker_config = configs
+            .into_iter()
+            .next()
+            .expect("broker config")
+            .map_err(|code| format!("describe configs failed: {code}"))?;
+        let compression_type = broker_config
+            .get("compression.type")
+            .and_then(|entry| entry.value.clone());
+        assert_eq!(compression_type.as_deref(), Some("gzip"));
+
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &bootstrap_servers)
+            .set("message.timeout.ms", "5000")
+            .create::<FutureProducer>()
+            .expect("Failed to create Kafka FutureProducer");
+        let consumer = ClientConfig::new()
+            .set("group.id", "testcontainer-rs")
+            .set("bootstrap.servers", &bootstrap_servers)
+            .set("auto.offset.reset", "earliest")
+            .create::<StreamConsumer>()
+            .expect("Failed to create Kafka StreamConsumer");
+
+        let topic = "compressed-topic";
+        producer
+            .send(
+                FutureRecord::<(), _>::to(topic).payload("compressed"),
+                Duration::from_secs(0),
+            )
+            .await
+            .map_err(|(e, _)| e)?;
+
+        consumer.subscribe(&[topic])?;
+        let message = tokio::time::timeout(Duration::from_secs(10), consumer.recv()).await??;
+        assert_eq!(message.payload_view::<str>(), Some(Ok("compressed")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn produce_with_message_max_bytes() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let max_bytes = 2 * 1024 * 1024;
+        let kafka_node = kafka::Kafka::default()
+            .with_message_max_bytes(max_bytes)
+            .start()
+            .await?;
+
+        let bootstrap_servers = kafka::bootstrap_servers(&kafka_node).await?;
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &bootstrap_servers)
+            .set("message.timeout.ms", "5000")
+            // let the broker reject oversized messages instead of the client
+            .set("message.max.bytes", (4 * max_bytes).to_string())
+            .create::<FutureProducer>()
+            .expect("Failed to create Kafka FutureProducer");
+
+        let topic = "large-messages";
+        let below_limit = vec![b'a'; max_bytes as usize - 1024];
+        producer
+            .send(
+                FutureRecord::<(), _>::to(topic).payload(&below_limit),
+                Duration::from_secs(0),
+            )
+            .await
+            .map_err(|(e, _)| e)?;
+
+        let above_limit = vec![b'a'; max_bytes as usize + 1024];
+        let result = producer
+            .send(
+                FutureRecord::<(), _>::to(topic).payload(&above_limit),
+                Duration::from_secs(0),
+            )
+            .await;
+        match result {
+            Err((KafkaError::MessageProduction(code), _)) => {
+                assert_eq!(code, RDKafkaErrorCode::MessageSizeTooLarge)
+            }
+            other => panic!("expected the broker to reject the message, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn auto_created_topic_with_default_partitions(
+    ) -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let kafka_node = kafka::Kafka::default()
+            .with_default_partitions(3)
+            .start()
+            .await?;
+
+        let client_config = kafka_node.image().client_config(
+            kafka_node.get_host().await?,
+            kafka_node.get_host_port_ipv4(kafka::KAFKA_PORT).await?,
+        );
+        let producer = ClientConfig::from_iter(client_config)
+            .set("message.timeout.ms", "5000")
+            .create::<FutureProducer>()
+            .expect("Failed to create Kafka FutureProducer");
+
+        let topic = "auto-created-topic";
+        producer
+            .send(
+                FutureRecord::<(), _>::to(topic).payload("auto-created"),
+                Duration::from_secs(0),
+            )
+            .await
+            .map_err(|(e, _)| e)?;
+
+        let metadata = producer
+            .client()
+            .fetch_metadata(Some(topic), Duration::from_secs(5))?;
+        let topic_metadata = &metadata.topics()[0];
+        assert_eq!(topic_metadata.name(), topic);
+        assert_eq!(topic_metadata.partitions().len(), 3);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn segment_files_in_log_dir() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _ = pretty_env_logger::try_init();
+        let log_dir = tempfile::tempdir()?;
+        // the broker doesn't run as root
+        std::fs::set_permissions(log_dir.path(), std::fs::Permissions::from_mode(0o777))?;
+        let kafka_node = kafka::Kafka::default()
+            .with_log_dir(log_dir.path())
+            .start()
+            .await?;
+
+        let client_config = kafka_node.image().client_config(
+            kafka_node.get_host().await?,
+            kafka_node.get_host_port_ipv4(kafka::KAFKA_PORT).await?,
+        );
+        let producer = ClientConfig::from_iter(client_config)
+            .set("message.timeout.ms", "5000")
+            .create::<FutureProducer>()
+            .expect("Failed to create Kafka FutureProducer");
+
+        let topic = "log-dir-topic";
+        for i in 0..5 {
+            producer
+                .send(
+                    FutureRecord::<(), _>::to(topic).payload(&format!("Message {i}")),
+                    Duration::from_secs(0),
+                )
+                .await
+                .map_err(|(e, _)| e)?;
+        }
+
+        let segments = std::fs::read_dir(log_dir.path().join(format!("{topic}-0")))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let log_segment = segments
+            .iter()
+            .find(|path| path.extension().is_some_and(|ext| ext == "log"))
+            .unwrap_or_else(|| panic!("no segment file found in {segments:?}"));
+        assert!(std::fs::metadata(log_segment)?.len() > 0);
+
+        // the directory is removed once dropped, after the broker stopped writing to it
+        kafka_node.rm().await?;
+        Ok(())
+    }
 }
@@ -1,6 +1,115 @@
+//! # Advertised host
+//!
+//! The broker advertises a host to clients outside of the container, which is derived separately
+//! from `get_host` of the started container, since the `ContainerState` passed to `exec_after_start`
+//! has no host. Only these two environment variables are honoured:
+//! - `TESTCONTAINERS_HOST_OVERRIDE`, if set;
+//! - otherwise `DOCKER_HOST`, whose host is advertised for a `tcp`, `http` or `https` URL,
+//!   and `127.0.0.1` for a local socket.
+//!
+//! For the usual Docker setups, it's the same host `get_host` returns. Unlike `get_host`,
+//! the `tc.host` of `~/.testcontainers.properties` and the gateway used when the tests themselves
+//! run inside of a container are ignored, so clients are redirected to the wrong host then.
+//! Set `TESTCONTAINERS_HOST_OVERRIDE` to the host returned by `get_host` in these setups.
+
+use std::{collections::HashMap, fmt::Display};
+
 /// Test container based on Apache Kafka Image
 pub mod apache;
 /// Test container based on Confluent Kafka Image
 pub mod confluent;
 
 pub use confluent::*;
+
+/// Returns the host the broker advertises to clients outside of the container,
+/// see the [module docs](self#advertised-host).
+fn advertised_host() -> String {
+    advertised_host_of(
+        std::env::var("TESTCONTAINERS_HOST_OVERRIDE")
+            .ok()
+            .as_deref(),
+        std::env::var("DOCKER_HOST").ok().as_deref(),
+    )
+}
+
+/// Returns the `bootstrap.servers` of a broker reachable on `host_port` of `host`,
+/// shared by both images.
+fn bootstrap_servers_of(host: impl Display, host_port: u16) -> String {
+    format!("{host}:{host_port}")
+}
+
+/// Returns the client properties to connect to a `PLAINTEXT` listener reachable on `host_port`
+/// of `host`, shared by both images.
+fn client_config_of(host: impl Display, host_port: u16) -> HashMap<String, String> {
+    HashMap::from([
+        (
+            "bootstrap.servers".to_owned(),
+            bootstrap_servers_of(host, host_port),
+        ),
+        ("security.protocol".to_owned(), "PLAINTEXT".to_owned()),
+    ])
+}
+
+fn advertised_host_of(host_override: Option<&str>, docker_host: Option<&str>) -> String {
+    if let Some(host) = host_override.filter(|host| !host.is_empty()) {
+        return host.to_owned();
+    }
+    docker_host
+        .and_then(|docker_host| {
+            ["tcp://", "http://", "https://"]
+                .iter()
+                .find_map(|scheme| docker_host.strip_prefix(scheme))
+        })
+        .and_then(|authority| {
+            let authority = authority.split('/').next().unwrap_or_default();
+            match authority.strip_prefix('[') {
+                // IPv6 addresses are enclosed in brackets, and stay so in the listener
+                Some(ipv6) => ipv6.split(']').next().map(|ip| format!("[{ip}]")),
+                None => authority.split(':').next().map(str::to_owned),
+            }
+        })
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "127.0.0.1".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{advertised_host_of, client_config_of};
+
+    #[test]
+    fn client_config_of_plaintext_listener() {
+        let config = client_config_of("docker.example.com", 32768);
+        assert_eq!(config.len(), 2);
+        assert_eq!(config["bootstrap.servers"], "docker.example.com:32768");
+        assert_eq!(config["security.protocol"], "PLAINTEXT");
+    }
+
+    #[test]
+    fn advertised_host_of_docker_host() {
+        assert_eq!(advertised_host_of(None, None), "127.0.0.1");
+        assert_eq!(
+            advertised_host_of(None, Some("unix:///var/run/docker.sock")),
+            "127.0.0.1"
+        );
+        assert_eq!(
+            advertised_host_of(None, Some("tcp://docker.example.com:2376")),
+            "docker.example.com"
+        );
+        assert_eq!(
+            advertised_host_of(None, Some("http://10.0.0.5")),
+            "10.0.0.5"
+        );
+        assert_eq!(advertised_host_of(None, Some("tcp://[::1]:2375")), "[::1]");
+        assert_eq!(
+            advertised_host_of(
+                Some("host.docker.internal"),
+                Some("tcp://docker.example.com:2376")
+            ),
+            "host.docker.internal"
+        );
+        assert_eq!(
+            advertised_host_of(Some(""), Some("tcp://docker.example.com:2376")),
+            "docker.example.com"
+        );
+    }
+}
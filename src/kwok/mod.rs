@@ -1,6 +1,10 @@
+use std::fmt::Display;
+
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
     core::{ContainerPort, WaitFor},
-    Image,
+    ContainerAsync, Image, TestcontainersError,
 };
 
 const NAME: &str = "registry.k8s.io/kwok/cluster";
@@ -11,6 +15,8 @@ const DEFAULT_WAIT: u64 = 3000;
 ///
 /// [`Kwok Cluster`]: https://kwok.sigs.k8s.io/
 pub const KWOK_CLUSTER_PORT: ContainerPort = ContainerPort::Tcp(8080);
+/// Name of the cluster, the context and the user of [`KwokCluster::kube_config`]
+pub const KWOK_CONTEXT_NAME: &str = "kwok-kwok";
 
 /// This module provides [`Kwok Cluster`] (Kubernetes WithOut Kubelet).
 ///
@@ -31,6 +37,11 @@ pub const KWOK_CLUSTER_PORT: ContainerPort = ContainerPort::Tcp(8080);
 ///
 /// No environment variables are required.
 ///
+/// # Connecting
+///
+/// The API server is served over plain HTTP without authentication. [`kube_config`] returns a kube config
+/// pointing to it, which can be loaded with e.g. `kube::config::Kubeconfig::from_yaml`.
+///
 /// [`Kwok Cluster`]: https://kwok.sigs.k8s.io/
 #[derive(Debug, Default, Clone)]
 pub struct KwokCluster {
@@ -40,6 +51,54 @@ pub struct KwokCluster {
     _priv: (),
 }
 
+impl KwokCluster {
+    /// Returns a kube config in YAML with a single no-auth context named [`KWOK_CONTEXT_NAME`],
+    /// with `host_port` being the host port [`KWOK_CLUSTER_PORT`] is mapped to.
+    ///
+    /// See [`kube_config`] and [`kube_config_blocking`] to get the config of a running container.
+    pub fn kube_config(&self, host: impl Display, host_port: u16) -> String {
+        format!(
+            r#"apiVersion: v1
+kind: Config
+clusters:
+- name: {KWOK_CONTEXT_NAME}
+  cluster:
+    server: http://{host}:{host_port}
+contexts:
+- name: {KWOK_CONTEXT_NAME}
+  context:
+    cluster: {KWOK_CONTEXT_NAME}
+    user: {KWOK_CONTEXT_NAME}
+current-context: {KWOK_CONTEXT_NAME}
+users:
+- name: {KWOK_CONTEXT_NAME}
+  user: {{}}
+"#
+        )
+    }
+}
+
+/// Returns the [`KwokCluster::kube_config`] of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`KWOK_CLUSTER_PORT`].
+pub async fn kube_config(
+    container: &ContainerAsync<KwokCluster>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(KWOK_CLUSTER_PORT).await?;
+    Ok(container.image().kube_config(host, port))
+}
+
+/// Returns the [`KwokCluster::kube_config`] of a container started with the `SyncRunner`,
+/// using the host port mapped to [`KWOK_CLUSTER_PORT`].
+#[cfg(feature = "blocking")]
+pub fn kube_config_blocking(
+    container: &Container<KwokCluster>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(KWOK_CLUSTER_PORT)?;
+    Ok(container.image().kube_config(host, port))
+}
+
 impl Image for KwokCluster {
     fn name(&self) -> &str {
         NAME
@@ -67,17 +126,27 @@ mod test {
     use kube::{
         api::ListParams,
         client::Client,
-        config::{AuthInfo, Cluster, KubeConfigOptions, Kubeconfig, NamedAuthInfo, NamedCluster},
+        config::{KubeConfigOptions, Kubeconfig},
         Api, Config,
     };
     use rustls::crypto::CryptoProvider;
-    use testcontainers::core::IntoContainerPort;
 
-    use crate::{kwok::KwokCluster, testcontainers::runners::AsyncRunner};
+    use crate::{
+        kwok::{kube_config, KwokCluster, KWOK_CONTEXT_NAME},
+        testcontainers::runners::AsyncRunner,
+    };
 
-    const CLUSTER_NAME: &str = "kwok-kwok";
-    const CONTEXT_NAME: &str = "kwok-kwok";
-    const CLUSTER_USER: &str = "kwok-kwok";
+    #[test]
+    fn kwok_kube_config() {
+        let kubeconfig =
+            Kubeconfig::from_yaml(&KwokCluster::default().kube_config("localhost", 18080)).unwrap();
+        assert_eq!(
+            kubeconfig.current_context.as_deref(),
+            Some(KWOK_CONTEXT_NAME)
+        );
+        let cluster = kubeconfig.clusters[0].cluster.as_ref().unwrap();
+        assert_eq!(cluster.server.as_deref(), Some("http://localhost:18080"));
+    }
 
     #[tokio::test]
     async fn test_kwok_image() -> Result<(), Box<dyn std::error::Error + 'static>> {
@@ -88,47 +157,11 @@ mod test {
         }
 
         let node = KwokCluster::default().start().await?;
-        let host_port = node.get_host_port_ipv4(8080.tcp()).await?;
-
-        // Create a custom Kubeconfig
-        let kubeconfig = Kubeconfig {
-            clusters: vec![NamedCluster {
-                name: String::from(CLUSTER_NAME),
-                cluster: Some(Cluster {
-                    server: Some(format!("http://localhost:{host_port}")), // your custom endpoint
-                    ..Default::default()
-                }),
-            }],
-            contexts: vec![kube::config::NamedContext {
-                name: CONTEXT_NAME.to_string(),
-                context: Option::from(kube::config::Context {
-                    cluster: CLUSTER_NAME.to_string(),
-                    user: String::from(CLUSTER_USER),
-                    ..Default::default()
-                }),
-            }],
-            auth_infos: vec![NamedAuthInfo {
-                name: String::from(CLUSTER_USER),
-                auth_info: Some(AuthInfo {
-                    token: None,
-                    ..Default::default()
-                }),
-            }],
-            current_context: Some(CONTEXT_NAME.to_string()),
-            ..Default::default()
-        };
-        let kubeconfigoptions = KubeConfigOptions {
-            context: Some(CONTEXT_NAME.to_string()),
-            cluster: Some(CLUSTER_NAME.to_string()),
-            user: None,
-        };
-
-        // Convert the Kubeconfig into a Config
-        let config = Config::from_custom_kubeconfig(kubeconfig, &kubeconfigoptions)
+
+        let kubeconfig = Kubeconfig::from_yaml(&kube_config(&node).await?)?;
+        let config = Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default())
             .await
             .unwrap();
-
-        // Create a Client from Config
         let client = Client::try_from(config).unwrap();
 
         let api: Api<Namespace> = Api::all(client);
@@ -169,5 +169,23 @@ pub mod victoria_metrics;
 /// **Apache ZooKeeper** (locking and configuratin management) testcontainer
 pub mod zookeeper;
 
+mod error;
+pub use error::ModuleConfigError;
+mod health;
+pub use health::{HealthProbe, ModuleHealthExt};
+mod image_reference;
+pub use image_reference::ImageReference;
+mod resources;
+pub use resources::ResourceLimits;
+#[cfg(any(
+    feature = "gitea",
+    feature = "hashicorp_vault",
+    feature = "meilisearch",
+    feature = "neo4j"
+))]
+mod tls;
+// helpers shared by the modules
+mod util;
+
 /// Re-exported version of `testcontainers` to avoid version conflicts
 pub use testcontainers;
@@ -1,13 +1,22 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
-use testcontainers::{core::WaitFor, CopyDataSource, CopyToContainer, Image};
+use testcontainers::{
+    core::{ContainerPort, WaitFor},
+    CopyDataSource, CopyToContainer, Image,
+};
 
 const NAME: &str = "mariadb";
 const TAG: &str = "11.3";
+/// Port that the [`MariaDB`] server listens on inside of the container
+///
+/// [`MariaDB`]: https://www.mariadb.com/
+pub const MARIADB_PORT: ContainerPort = ContainerPort::Tcp(3306);
 
 /// Module to work with [`MariaDB`] inside of tests.
 ///
 /// Starts an instance of MariaDB with no password set for the root user and a default database named `test` created.
+/// Credentials and the database can be changed with [`Mariadb::with_database`], [`Mariadb::with_user`],
+/// [`Mariadb::with_password`] and [`Mariadb::with_root_password`].
 ///
 /// This module is based on the official [`MariaDB docker image`].
 ///
@@ -19,18 +28,61 @@ const TAG: &str = "11.3";
 /// let mariadb_url = format!(
 ///     "mariadb://{}:{}/test",
 ///     mariadb_instance.get_host().unwrap(),
-///     mariadb_instance.get_host_port_ipv4(3306).unwrap(),
+///     mariadb_instance
+///         .get_host_port_ipv4(mariadb::MARIADB_PORT)
+///         .unwrap(),
 /// );
 /// ```
 ///
 /// [`MariaDB`]: https://www.mariadb.com/
 /// [`MariaDB docker image`]: https://hub.docker.com/_/mariadb
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Mariadb {
+    env_vars: HashMap<String, String>,
+    allow_empty_password: Option<bool>,
     copy_to_sources: Vec<CopyToContainer>,
 }
 
 impl Mariadb {
+    /// Sets the name of the database created on startup (`MARIADB_DATABASE`).
+    /// Default: `test`
+    pub fn with_database(mut self, database: &str) -> Self {
+        self.env_vars
+            .insert("MARIADB_DATABASE".to_owned(), database.to_owned());
+        self
+    }
+
+    /// Creates a non-root user (`MARIADB_USER`), which is granted all privileges on the database.
+    /// Its password is set with [`Mariadb::with_password`].
+    pub fn with_user(mut self, user: &str) -> Self {
+        self.env_vars
+            .insert("MARIADB_USER".to_owned(), user.to_owned());
+        self
+    }
+
+    /// Sets the password of the user set with [`Mariadb::with_user`] (`MARIADB_PASSWORD`).
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.env_vars
+            .insert("MARIADB_PASSWORD".to_owned(), password.to_owned());
+        self
+    }
+
+    /// Sets the password of the root user (`MARIADB_ROOT_PASSWORD`).
+    ///
+    /// The root user has no password by default, which is no longer allowed once this is set.
+    pub fn with_root_password(mut self, root_password: &str) -> Self {
+        self.env_vars
+            .insert("MARIADB_ROOT_PASSWORD".to_owned(), root_password.to_owned());
+        self
+    }
+
+    /// Explicitly allows the root user to have no password (`MARIADB_ALLOW_EMPTY_ROOT_PASSWORD`),
+    /// which is the default unless [`Mariadb::with_root_password`] is set.
+    pub fn with_empty_root_password(mut self) -> Self {
+        self.allow_empty_password = Some(true);
+        self
+    }
+
     /// Registers sql to be executed automatically when the container starts.
     /// Can be called multiple times to add (not override) scripts.
     ///
@@ -59,6 +111,30 @@ impl Mariadb {
             .push(CopyToContainer::new(init_sql.into(), target));
         self
     }
+
+    /// Sets the host the root user is allowed to connect from, via `MARIADB_ROOT_HOST`.
+    ///
+    /// By default, root can connect from any host (`%`).
+    /// Setting it to `localhost` restricts root to connections from inside the container,
+    /// so connecting through the mapped port is refused.
+    pub fn with_root_host(mut self, host: impl Into<String>) -> Self {
+        self.env_vars
+            .insert("MARIADB_ROOT_HOST".to_owned(), host.into());
+        self
+    }
+}
+
+impl Default for Mariadb {
+    fn default() -> Self {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("MARIADB_DATABASE".to_owned(), "test".to_owned());
+
+        Self {
+            env_vars,
+            allow_empty_password: None,
+            copy_to_sources: Vec::new(),
+        }
+    }
 }
 
 impl Image for Mariadb {
@@ -70,6 +146,10 @@ impl Image for Mariadb {
         TAG
     }
 
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &[MARIADB_PORT]
+    }
+
     fn ready_conditions(&self) -> Vec<WaitFor> {
         vec![
             WaitFor::message_on_stderr("mariadbd: ready for connections."),
@@ -80,11 +160,19 @@ impl Image for Mariadb {
     fn env_vars(
         &self,
     ) -> impl IntoIterator<Item = (impl Into<Cow<'_, str>>, impl Into<Cow<'_, str>>)> {
-        [
-            ("MARIADB_DATABASE", "test"),
-            ("MARIADB_ALLOW_EMPTY_ROOT_PASSWORD", "1"),
-        ]
+        let mut vars = self.env_vars.clone();
+        let allow_empty_password = self
+            .allow_empty_password
+            .unwrap_or_else(|| !vars.contains_key("MARIADB_ROOT_PASSWORD"));
+        if allow_empty_password {
+            vars.insert(
+                "MARIADB_ALLOW_EMPTY_ROOT_PASSWORD".to_owned(),
+                "1".to_owned(),
+            );
+        }
+        vars
     }
+
     fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
         &self.copy_to_sources
     }
@@ -92,11 +180,19 @@ impl Image for Mariadb {
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        borrow::Cow,
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
     use mysql::prelude::Queryable;
-    use testcontainers::core::IntoContainerPort;
+    use testcontainers::{core::logs::LogFrame, Image};
 
     use crate::{
-        mariadb::Mariadb as MariadbImage,
+        mariadb::{Mariadb as MariadbImage, MARIADB_PORT},
         testcontainers::{runners::SyncRunner, ImageExt},
     };
 
@@ -113,7 +209,7 @@ mod tests {
         let connection_string = &format!(
             "mysql://root@{}:{}/test",
             node.get_host()?,
-            node.get_host_port_ipv4(3306.tcp())?
+            node.get_host_port_ipv4(MARIADB_PORT)?
         );
         let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
 
@@ -132,7 +228,7 @@ mod tests {
         let connection_string = &format!(
             "mysql://root@{}:{}/test",
             node.get_host()?,
-            node.get_host_port_ipv4(3306.tcp())?
+            node.get_host_port_ipv4(MARIADB_PORT)?
         );
         let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
 
@@ -152,7 +248,7 @@ mod tests {
         let connection_string = &format!(
             "mysql://root@{}:{}/test",
             node.get_host()?,
-            node.get_host_port_ipv4(3306.tcp())?
+            node.get_host_port_ipv4(MARIADB_PORT)?
         );
 
         let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
@@ -165,4 +261,109 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn mariadb_root_password_disallows_empty_password() {
+        let env_vars = |mariadb: &MariadbImage| -> HashMap<String, String> {
+            mariadb
+                .env_vars()
+                .into_iter()
+                .map(|(k, v)| {
+                    let (k, v): (Cow<'_, str>, Cow<'_, str>) = (k.into(), v.into());
+                    (k.into_owned(), v.into_owned())
+                })
+                .collect()
+        };
+
+        let defaults = env_vars(&MariadbImage::default());
+        assert_eq!(defaults["MARIADB_ALLOW_EMPTY_ROOT_PASSWORD"], "1");
+
+        let with_root_password = env_vars(&MariadbImage::default().with_root_password("secret"));
+        assert!(!with_root_password.contains_key("MARIADB_ALLOW_EMPTY_ROOT_PASSWORD"));
+        assert_eq!(with_root_password["MARIADB_ROOT_PASSWORD"], "secret");
+
+        let opted_in = env_vars(
+            &MariadbImage::default()
+                .with_empty_root_password()
+                .with_root_password("secret"),
+        );
+        assert_eq!(opted_in["MARIADB_ALLOW_EMPTY_ROOT_PASSWORD"], "1");
+    }
+
+    #[test]
+    fn mariadb_custom_credentials() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = MariadbImage::default()
+            .with_database("app_db")
+            .with_user("app")
+            .with_password("app-password")
+            .with_root_password("root-password")
+            .start()?;
+
+        let host = node.get_host()?;
+        let port = node.get_host_port_ipv4(MARIADB_PORT)?;
+        let connection_string = &format!("mysql://app:app-password@{host}:{port}/app_db");
+        let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
+
+        let row: Option<(String, String)> = conn
+            .query_first("SELECT SUBSTRING_INDEX(CURRENT_USER(), '@', 1), DATABASE()")
+            .unwrap();
+        assert_eq!(row, Some(("app".to_owned(), "app_db".to_owned())));
+
+        // root has a password now
+        let root_connection_string = &format!("mysql://root@{host}:{port}/app_db");
+        assert!(mysql::Conn::new(mysql::Opts::from_url(root_connection_string).unwrap()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn mariadb_root_host() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = MariadbImage::default()
+            .with_root_host("localhost")
+            .start()?;
+
+        // the mapped port is reached from outside the container, which isn't `localhost`
+        let connection_string = &format!(
+            "mysql://root@{}:{}/test",
+            node.get_host()?,
+            node.get_host_port_ipv4(MARIADB_PORT)?
+        );
+        let result = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap());
+        assert!(result.is_err(), "root should be restricted to localhost");
+        Ok(())
+    }
+
+    #[test]
+    fn mariadb_log_consumer_observes_ready_line() -> Result<(), Box<dyn std::error::Error + 'static>>
+    {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        let collected = lines.clone();
+        let _node = MariadbImage::default()
+            .with_log_consumer(move |frame: &LogFrame| {
+                collected
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(frame.bytes()).into_owned());
+            })
+            .start()?;
+
+        // the consumer runs on its own task, so give it a moment to catch up
+        let observed = (0..50).any(|_| {
+            let found = lines
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|line| line.contains("mariadbd: ready for connections."));
+            if !found {
+                thread::sleep(Duration::from_millis(100));
+            }
+            found
+        });
+        assert!(observed, "log consumer did not observe the readiness line");
+        Ok(())
+    }
+
+    #[test]
+    fn mariadb_expose_ports() {
+        assert_eq!(MariadbImage::default().expose_ports(), [MARIADB_PORT]);
+    }
 }
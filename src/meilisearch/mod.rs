@@ -1,18 +1,37 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, fmt::Display, path::Path};
 
 use parse_display::{Display, FromStr};
+use percent_encoding::utf8_percent_encode;
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
-    core::{wait::HttpWaitStrategy, ContainerPort, WaitFor},
-    Image,
+    core::{
+        wait::HttpWaitStrategy, CmdWaitFor, ContainerPort, ContainerState, ExecCommand, Mount,
+        WaitFor,
+    },
+    ContainerAsync, CopyDataSource, CopyToContainer, Image, TestcontainersError,
+};
+
+use crate::{
+    tls::{https_client, GeneratedCert},
+    util::{http_ready, PATH_SEGMENT},
+    HealthProbe, ModuleConfigError,
 };
 
 const NAME: &str = "getmeili/meilisearch";
 const TAG: &str = "v1.8.3";
+const TLS_CERT_PATH: &str = "/meili_ssl/cert.pem";
+const TLS_KEY_PATH: &str = "/meili_ssl/key.pem";
+const TLS_CA_PATH: &str = "/meili_ssl/ca.pem";
+const IMPORT_DUMP_PATH: &str = "/meili_import/import.dump";
+const IMPORT_SNAPSHOT_PATH: &str = "/meili_import/import.snapshot";
 /// Port that the [`Meilisearch`] container has internally
 /// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
 ///
 /// [`Meilisearch`]: https://www.meilisearch.com
 pub const MEILISEARCH_PORT: ContainerPort = ContainerPort::Tcp(7700);
+/// Directory inside of the container where [`Meilisearch::with_db_path`] mounts the database
+pub const MEILISEARCH_DB_PATH: &str = "/data.ms";
 
 /// Module to work with [`Meilisearch`] inside of tests.
 ///
@@ -21,6 +40,8 @@ pub const MEILISEARCH_PORT: ContainerPort = ContainerPort::Tcp(7700);
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
 /// use testcontainers_modules::{meilisearch, testcontainers::runners::SyncRunner};
 ///
 /// let meilisearch_instance = meilisearch::Meilisearch::default().start().unwrap();
@@ -28,16 +49,30 @@ pub const MEILISEARCH_PORT: ContainerPort = ContainerPort::Tcp(7700);
 /// let dashboard = format!(
 ///     "http://{}:{}",
 ///     meilisearch_instance.get_host().unwrap(),
-///     meilisearch_instance.get_host_port_ipv4(7700).unwrap()
+///     meilisearch_instance
+///         .get_host_port_ipv4(meilisearch::MEILISEARCH_PORT)
+///         .unwrap()
 /// );
+/// // or, equivalently
+/// let dashboard = meilisearch::base_url_blocking(&meilisearch_instance).unwrap();
+/// # }
 /// ```
 ///
+/// The URL and [`Meilisearch::master_key`] are all that's needed to construct a client, e.g.
+/// `meilisearch_sdk::client::Client::new(base_url(&container).await?, container.image().master_key())`.
+///
 /// [`Meilisearch`]: https://www.meilisearch.com/
 /// [`Meilisearch docker docs`]: https://www.meilisearch.com/docs/guides/misc/docker
 /// [`Meilisearch docker image`]: https://hub.docker.com/_/getmeili/meilisearch
 #[derive(Debug, Clone)]
 pub struct Meilisearch {
     env_vars: HashMap<String, String>,
+    auth_ready_check: bool,
+    tls: Option<GeneratedCert>,
+    copy_to_sources: Vec<CopyToContainer>,
+    import_source: Option<CopyToContainer>,
+    db_mount: Option<Mount>,
+    search_defaults: Vec<(String, u32)>,
 }
 
 /// Sets the environment of the [`Meilisearch`] instance.
@@ -121,6 +156,196 @@ impl Meilisearch {
             .insert("MEILI_LOG_LEVEL".to_owned(), level.to_string());
         self
     }
+
+    /// Additionally waits for an authenticated `/keys` request to succeed before the
+    /// [`Meilisearch`] instance is considered ready.
+    ///
+    /// The `/health` endpoint is public, so by default the container may be reported as ready
+    /// before requests authenticated with the master key are accepted.
+    /// Has no effect if no master key is set via [Meilisearch::with_master_key].
+    pub fn with_auth_ready_check(mut self) -> Self {
+        self.auth_ready_check = true;
+        self
+    }
+
+    /// Serves the [`Meilisearch`] API over HTTPS only, using a generated self-signed certificate.
+    ///
+    /// The certificate is valid for `localhost`, `127.0.0.1` and `::1`, and its issuing CA
+    /// can be obtained with [Meilisearch::tls_ca].
+    ///
+    /// See the [official docs for this option](https://www.meilisearch.com/docs/learn/configuration/instance_options#ssl-certificates-path)
+    pub fn with_tls(mut self) -> Self {
+        let tls = GeneratedCert::new("Meilisearch root CA", &[]);
+        self.env_vars
+            .insert("MEILI_SSL_CERT_PATH".to_owned(), TLS_CERT_PATH.to_owned());
+        self.env_vars
+            .insert("MEILI_SSL_KEY_PATH".to_owned(), TLS_KEY_PATH.to_owned());
+        self.copy_to_sources = vec![
+            CopyToContainer::new(
+                CopyDataSource::Data(tls.cert.clone().into_bytes()),
+                TLS_CERT_PATH,
+            ),
+            CopyToContainer::new(
+                CopyDataSource::Data(tls.key.clone().into_bytes()),
+                TLS_KEY_PATH,
+            ),
+            // trusted by the requests made inside of the container
+            CopyToContainer::new(
+                CopyDataSource::Data(tls.ca.clone().into_bytes()),
+                TLS_CA_PATH,
+            ),
+        ];
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Starts the [`Meilisearch`] instance from a dump, e.g. created with the `/dumps` API
+    /// of another instance. Replaces a snapshot set via [Meilisearch::with_import_snapshot].
+    ///
+    /// The instance reports to be healthy only after the import has finished, so a large dump may
+    /// exceed the default startup timeout; raise it with
+    /// [`ImageExt::with_startup_timeout`](testcontainers::ImageExt::with_startup_timeout) if needed.
+    ///
+    /// See the [official docs for this option](https://www.meilisearch.com/docs/learn/configuration/instance_options#import-dump)
+    pub fn with_import_dump(mut self, source: impl Into<CopyDataSource>) -> Self {
+        self.env_vars.remove("MEILI_IMPORT_SNAPSHOT");
+        self.env_vars
+            .insert("MEILI_IMPORT_DUMP".to_owned(), IMPORT_DUMP_PATH.to_owned());
+        self.import_source = Some(CopyToContainer::new(source, IMPORT_DUMP_PATH));
+        self
+    }
+
+    /// Starts the [`Meilisearch`] instance from a snapshot of another instance.
+    /// Replaces a dump set via [Meilisearch::with_import_dump].
+    ///
+    /// As with dumps, the instance reports to be healthy only after the import has finished.
+    ///
+    /// See the [official docs for this option](https://www.meilisearch.com/docs/learn/configuration/instance_options#import-snapshot)
+    pub fn with_import_snapshot(mut self, source: impl Into<CopyDataSource>) -> Self {
+        self.env_vars.remove("MEILI_IMPORT_DUMP");
+        self.env_vars.insert(
+            "MEILI_IMPORT_SNAPSHOT".to_owned(),
+            IMPORT_SNAPSHOT_PATH.to_owned(),
+        );
+        self.import_source = Some(CopyToContainer::new(source, IMPORT_SNAPSHOT_PATH));
+        self
+    }
+
+    /// Stores the database of the [`Meilisearch`] instance in `host_dir`, bind-mounted to
+    /// [`MEILISEARCH_DB_PATH`], so indexes and documents outlive the container.
+    ///
+    /// Only one instance may use the database at a time, so stop a container before starting
+    /// another one with the same `host_dir`.
+    ///
+    /// See the [official docs for this option](https://www.meilisearch.com/docs/learn/configuration/instance_options#database-path)
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [`Meilisearch::try_with_db_path`].
+    pub fn with_db_path(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_db_path(host_dir)
+            .expect("database directory is not valid UTF-8")
+    }
+
+    /// Same as [`Meilisearch::with_db_path`], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_db_path(
+        mut self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        self.env_vars
+            .insert("MEILI_DB_PATH".to_owned(), MEILISEARCH_DB_PATH.to_owned());
+        self.db_mount = Some(Mount::bind_mount(host_dir, MEILISEARCH_DB_PATH));
+        Ok(self)
+    }
+
+    /// Caps the number of hits a search of the index `index_uid` can return (`pagination.maxTotalHits`),
+    /// 1000 by default, which also caps `estimatedTotalHits` of search results.
+    /// Can be called multiple times to configure several indexes.
+    ///
+    /// Note that this is a per-index setting rather than an instance-wide default, since Meilisearch
+    /// has no instance-wide defaults for search settings. After start the settings of each index
+    /// are updated via the settings API, which creates the index if it doesn't exist.
+    /// Create the index with [`Meilisearch`] clients only if it doesn't exist, or just add documents to it.
+    ///
+    /// See the [official docs for this setting](https://www.meilisearch.com/docs/reference/api/settings#pagination)
+    pub fn with_search_defaults(mut self, index_uid: &str, max_total_hits: u32) -> Self {
+        self.search_defaults
+            .push((index_uid.to_owned(), max_total_hits));
+        self
+    }
+
+    /// Returns the PEM encoded CA certificate which issued the certificate of the [`Meilisearch`] instance,
+    /// if HTTPS is enabled via [Meilisearch::with_tls].
+    pub fn tls_ca(&self) -> Option<&str> {
+        self.tls.as_ref().map(|tls| tls.ca.as_str())
+    }
+
+    /// Returns the master key set via [Meilisearch::with_master_key], if any.
+    pub fn master_key(&self) -> Option<&str> {
+        self.env_vars.get("MEILI_MASTER_KEY").map(String::as_str)
+    }
+
+    /// Returns the base URL of the API and the dashboard, e.g. `http://127.0.0.1:7700`,
+    /// with `host_port` being the host port [`MEILISEARCH_PORT`] is mapped to.
+    /// The scheme is `https` if enabled via [Meilisearch::with_tls].
+    ///
+    /// See [`base_url`] and [`base_url_blocking`] to get the URL of a running container.
+    pub fn base_url(&self, host: impl Display, host_port: u16) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        format!("{scheme}://{host}:{host_port}")
+    }
+
+    /// Returns the master key the `/keys` readiness check of [`Meilisearch::with_auth_ready_check`]
+    /// authenticates with, if the check is done.
+    fn auth_ready_check_key(&self) -> Option<&str> {
+        self.master_key().filter(|_| self.auth_ready_check)
+    }
+
+    /// Returns the `curl` command to call the API inside of the container, failing on error responses,
+    /// trusting the generated CA if HTTPS is enabled and authenticated with the master key if set.
+    fn curl_cmd(&self) -> Vec<String> {
+        let mut cmd = vec!["curl".to_owned(), "-sf".to_owned()];
+        if self.tls.is_some() {
+            cmd.push("--cacert".to_owned());
+            cmd.push(TLS_CA_PATH.to_owned());
+        }
+        if let Some(master_key) = self.master_key() {
+            cmd.push("-H".to_owned());
+            cmd.push(format!("Authorization: Bearer {master_key}"));
+        }
+        cmd
+    }
+
+    /// Applies HTTPS to the given wait strategy, if enabled
+    fn with_wait_tls(&self, strategy: HttpWaitStrategy) -> HttpWaitStrategy {
+        let Some(tls) = &self.tls else {
+            return strategy;
+        };
+        strategy.with_tls().with_client(https_client(Some(&tls.ca)))
+    }
+}
+
+/// Returns the [`Meilisearch::base_url`] of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`MEILISEARCH_PORT`].
+pub async fn base_url(
+    container: &ContainerAsync<Meilisearch>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(MEILISEARCH_PORT).await?;
+    Ok(container.image().base_url(host, port))
+}
+
+/// Returns the [`Meilisearch::base_url`] of a container started with the `SyncRunner`,
+/// using the host port mapped to [`MEILISEARCH_PORT`].
+#[cfg(feature = "blocking")]
+pub fn base_url_blocking(
+    container: &Container<Meilisearch>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(MEILISEARCH_PORT)?;
+    Ok(container.image().base_url(host, port))
 }
 
 impl Default for Meilisearch {
@@ -133,7 +358,15 @@ impl Default for Meilisearch {
     fn default() -> Self {
         let mut env_vars = HashMap::new();
         env_vars.insert("MEILI_NO_ANALYTICS".to_owned(), "true".to_owned());
-        Self { env_vars }
+        Self {
+            env_vars,
+            auth_ready_check: false,
+            tls: None,
+            copy_to_sources: vec![],
+            import_source: None,
+            db_mount: None,
+            search_defaults: vec![],
+        }
     }
 }
 
@@ -149,11 +382,22 @@ impl Image for Meilisearch {
     fn ready_conditions(&self) -> Vec<WaitFor> {
         // the container does allow for turning off logging entirely and does not have a healthcheck
         // => using the `/health` endpoint is the best strategy
-        vec![WaitFor::http(
-            HttpWaitStrategy::new("/health")
-                .with_expected_status_code(200_u16)
-                .with_body(r#"{ "status": "available" }"#.as_bytes()),
-        )]
+        let mut conditions = vec![WaitFor::http(self.with_wait_tls(http_ready(
+            "/health",
+            MEILISEARCH_PORT,
+            r#"{ "status": "available" }"#,
+        )))];
+        if let Some(master_key) = self.auth_ready_check_key() {
+            conditions.push(WaitFor::http(
+                self.with_wait_tls(
+                    HttpWaitStrategy::new("/keys")
+                        .with_port(MEILISEARCH_PORT)
+                        .with_bearer_auth(master_key)
+                        .with_expected_status_code(200_u16),
+                ),
+            ));
+        }
+        conditions
     }
 
     fn env_vars(
@@ -165,15 +409,68 @@ impl Image for Meilisearch {
     fn expose_ports(&self) -> &[ContainerPort] {
         &[MEILISEARCH_PORT]
     }
+
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        self.copy_to_sources.iter().chain(&self.import_source)
+    }
+
+    fn mounts(&self) -> impl IntoIterator<Item = &Mount> {
+        self.db_mount.iter()
+    }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        let base_url = self.base_url("localhost", MEILISEARCH_PORT.as_u16());
+        let commands = self
+            .search_defaults
+            .iter()
+            .map(|(index_uid, max_total_hits)| {
+                let index_uid = utf8_percent_encode(index_uid, PATH_SEGMENT);
+                let mut cmd = self.curl_cmd();
+                cmd.extend([
+                    "-X".to_owned(),
+                    "PATCH".to_owned(),
+                    format!("{base_url}/indexes/{index_uid}/settings/pagination"),
+                    "-H".to_owned(),
+                    "Content-Type: application/json".to_owned(),
+                    "-d".to_owned(),
+                    format!(r#"{{"maxTotalHits": {max_total_hits}}}"#),
+                ]);
+                ExecCommand::new(cmd).with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+            })
+            .collect();
+        Ok(commands)
+    }
+}
+
+impl HealthProbe for Meilisearch {
+    /// Requests the `/keys` API with the master key if set, regardless of [Meilisearch::with_auth_ready_check],
+    /// and the `/health` endpoint otherwise.
+    fn health_probe(&self) -> Vec<String> {
+        let path = if self.master_key().is_some() {
+            "/keys"
+        } else {
+            "/health"
+        };
+        let mut probe = self.curl_cmd();
+        probe.push(format!(
+            "{}{path}",
+            self.base_url("localhost", MEILISEARCH_PORT.as_u16())
+        ));
+        probe
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use meilisearch_sdk::{client::Client, indexes::Index};
     use serde::{Deserialize, Serialize};
-    use testcontainers::{runners::AsyncRunner, ImageExt};
+    use testcontainers::{core::ExecCommand, runners::AsyncRunner, ImageExt};
 
     use super::*;
+    use crate::ModuleHealthExt;
     #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
     struct Movie {
         id: i64,
@@ -228,11 +525,7 @@ mod tests {
         let meilisearch_image = Meilisearch::default();
         let node = meilisearch_image.start().await?;
 
-        let connection_string = &format!(
-            "http://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(7700).await?,
-        );
+        let connection_string = &base_url(&node).await?;
         let auth: Option<String> = None; // not currently possible to type-infer String or that it is not nessesary
         let client = Client::new(connection_string, auth).unwrap();
 
@@ -265,6 +558,79 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn meilisearch_start_healthy() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let master_key = "secret master key";
+        let node = Meilisearch::default()
+            .with_master_key(master_key)
+            .start_healthy()
+            .await?;
+
+        let client = Client::new(base_url(&node).await?, Some(master_key)).unwrap();
+        // no retries needed, the probe has passed already
+        assert_eq!(client.health().await?.status, "available");
+        assert!(client.get_keys().await.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn meilisearch_search_defaults() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = Meilisearch::default()
+            .with_master_key("secret master key")
+            .with_search_defaults("movies", 4)
+            .start()
+            .await?;
+
+        let client = Client::new(base_url(&node).await?, node.image().master_key()).unwrap();
+
+        // the index has been created with the settings already
+        let movies = client.index("movies");
+        movies
+            .add_documents(&Movie::examples(), Some("id"))
+            .await?
+            .wait_for_completion(&client, None, None)
+            .await?;
+
+        let res = movies
+            .search()
+            .with_query("")
+            .with_limit(20)
+            .execute::<Movie>()
+            .await?;
+        assert_eq!(res.hits.len(), 4);
+        assert_eq!(res.estimated_total_hits, Some(4));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn meilisearch_search_defaults_tls() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = Meilisearch::default()
+            .with_tls()
+            .with_search_defaults("movies", 4)
+            .start()
+            .await?;
+
+        // the settings have been updated over HTTPS, trusting the generated CA
+        let ca = node.image().tls_ca().unwrap();
+        let client = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(ca.as_bytes())?)
+            .build()?;
+        let pagination = client
+            .get(format!(
+                "{}/indexes/movies/settings/pagination",
+                node.image().base_url(
+                    "localhost",
+                    node.get_host_port_ipv4(MEILISEARCH_PORT).await?
+                )
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(pagination["maxTotalHits"], 4);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn meilisearch_custom_version() -> Result<(), Box<dyn std::error::Error + 'static>> {
         let master_key = "secret master key".to_owned();
@@ -273,12 +639,8 @@ mod tests {
             .with_tag("v1.0");
         let node = meilisearch_image.start().await?;
 
-        let connection_string = &format!(
-            "http://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(7700).await?,
-        );
-        let client = Client::new(connection_string, Some(master_key)).unwrap();
+        let connection_string = &base_url(&node).await?;
+        let client = Client::new(connection_string, node.image().master_key()).unwrap();
 
         // insert documents and search for it
         let movies = Movie::get_index_with_loaded_examples(&client).await?;
@@ -306,12 +668,8 @@ mod tests {
             .with_master_key(&master_key);
         let node = meilisearch_image.start().await?;
 
-        let connection_string = &format!(
-            "http://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(7700).await?,
-        );
-        let client = Client::new(connection_string, Some(master_key)).unwrap();
+        let connection_string = &base_url(&node).await?;
+        let client = Client::new(connection_string, node.image().master_key()).unwrap();
 
         // insert documents and search for it
         let movies = Movie::get_index_with_loaded_examples(&client).await?;
@@ -328,4 +686,178 @@ mod tests {
         assert_eq!(result_ids, vec![3]);
         Ok(())
     }
+
+    #[test]
+    fn auth_ready_check_waits_for_keys() {
+        assert_eq!(Meilisearch::default().auth_ready_check_key(), None);
+        assert_eq!(
+            Meilisearch::default()
+                .with_master_key("secret master key")
+                .auth_ready_check_key(),
+            None
+        );
+        // no effect without a master key
+        assert_eq!(
+            Meilisearch::default()
+                .with_auth_ready_check()
+                .auth_ready_check_key(),
+            None
+        );
+        assert_eq!(
+            Meilisearch::default()
+                .with_master_key("secret master key")
+                .with_auth_ready_check()
+                .auth_ready_check_key(),
+            Some("secret master key")
+        );
+    }
+
+    #[tokio::test]
+    async fn meilisearch_auth_ready_check() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let master_key = "secret master key";
+        let node = Meilisearch::default()
+            .with_master_key(master_key)
+            .with_auth_ready_check()
+            .start()
+            .await?;
+
+        let keys = reqwest::Client::new()
+            .get(format!("{}/keys", base_url(&node).await?))
+            .bearer_auth(master_key)
+            .send()
+            .await?;
+        assert_eq!(keys.status(), 200);
+
+        let keys = keys.json::<serde_json::Value>().await?;
+        assert!(!keys["results"].as_array().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn meilisearch_tls() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = Meilisearch::default().with_tls().start().await?;
+
+        let ca = node.image().tls_ca().unwrap();
+        let client = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(ca.as_bytes())?)
+            .build()?;
+        let health = client
+            .get(format!(
+                "{}/health",
+                node.image().base_url(
+                    "localhost",
+                    node.get_host_port_ipv4(MEILISEARCH_PORT).await?
+                )
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(health["status"], "available");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn meilisearch_db_path_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/meilisearch-\xff"));
+        let result = Meilisearch::default().try_with_db_path(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
+    #[test]
+    fn meilisearch_base_url() {
+        let meilisearch = Meilisearch::default().with_master_key("secret");
+        assert_eq!(meilisearch.master_key(), Some("secret"));
+        assert_eq!(
+            meilisearch.base_url("127.0.0.1", 7700),
+            "http://127.0.0.1:7700"
+        );
+        assert_eq!(
+            meilisearch.with_tls().base_url("localhost", 17700),
+            "https://localhost:17700"
+        );
+        assert_eq!(Meilisearch::default().master_key(), None);
+    }
+
+    #[tokio::test]
+    async fn meilisearch_import_dump() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        // create a dump of an instance with the example movies
+        let source = Meilisearch::default().start().await?;
+        let client = Client::new(base_url(&source).await?, None::<String>)?;
+        Movie::get_index_with_loaded_examples(&client).await?;
+        client
+            .create_dump()
+            .await?
+            .wait_for_completion(&client, None, None)
+            .await?;
+        let mut dump = source
+            .exec(ExecCommand::new([
+                "sh",
+                "-c",
+                "cat /meili_data/dumps/*.dump",
+            ]))
+            .await?;
+        let dump = dump.stdout_to_vec().await?;
+        assert!(!dump.is_empty());
+
+        // and start another one from it
+        let node = Meilisearch::default()
+            .with_import_dump(dump)
+            .start()
+            .await?;
+        let client = Client::new(base_url(&node).await?, None::<String>)?;
+        let res = client
+            .index("movies")
+            .search()
+            .with_query("Dark Knig")
+            .execute::<Movie>()
+            .await?;
+        let result_ids = res
+            .hits
+            .into_iter()
+            .map(|r| r.result.id)
+            .collect::<Vec<i64>>();
+        assert_eq!(result_ids, vec![3]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn meilisearch_db_path() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let db_dir = std::env::temp_dir().join(format!("meilisearch-db-{}", std::process::id()));
+        std::fs::create_dir_all(&db_dir)?;
+
+        let node = Meilisearch::default().with_db_path(&db_dir).start().await?;
+        let client = Client::new(base_url(&node).await?, None::<String>)?;
+        Movie::get_index_with_loaded_examples(&client).await?;
+        node.rm().await?;
+
+        // a new instance on the same database still has the index and its documents
+        let node = Meilisearch::default().with_db_path(&db_dir).start().await?;
+        let client = Client::new(base_url(&node).await?, None::<String>)?;
+        let movies = client.get_index("movies").await?;
+        let stats = movies.get_stats().await?;
+        assert_eq!(stats.number_of_documents, Movie::examples().len());
+        let res = movies
+            .search()
+            .with_query("Dark Knig")
+            .execute::<Movie>()
+            .await?;
+        let result_ids = res
+            .hits
+            .into_iter()
+            .map(|r| r.result.id)
+            .collect::<Vec<i64>>();
+        assert_eq!(result_ids, vec![3]);
+
+        node.rm().await?;
+        // the files are owned by the user of the container, so this may fail
+        let _ = std::fs::remove_dir_all(&db_dir);
+        Ok(())
+    }
 }
@@ -1,12 +1,16 @@
 use std::{borrow::Cow, collections::HashMap};
 
-use testcontainers::{core::WaitFor, Image};
+use testcontainers::{
+    core::{CmdWaitFor, ContainerState, ExecCommand, WaitFor},
+    Image, TestcontainersError,
+};
 
 const NAME: &str = "minio/minio";
 const TAG: &str = "RELEASE.2022-02-07T08-17-33Z";
 
 const DIR: &str = "/data";
 const CONSOLE_ADDRESS: &str = ":9001";
+const MC_ALIAS: &str = "local";
 
 #[allow(missing_docs)]
 // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
@@ -14,6 +18,15 @@ const CONSOLE_ADDRESS: &str = ":9001";
 pub struct MinIO {
     env_vars: HashMap<String, String>,
     cmd: MinIOServerCmd,
+    notifications: Vec<BucketNotification>,
+}
+
+/// Webhook notification of a bucket, set up with `mc event add` after startup.
+#[derive(Debug, Clone)]
+struct BucketNotification {
+    bucket: String,
+    target_id: String,
+    events: String,
 }
 
 impl Default for MinIO {
@@ -27,10 +40,57 @@ impl Default for MinIO {
         Self {
             env_vars,
             cmd: MinIOServerCmd::default(),
+            notifications: vec![],
         }
     }
 }
 
+impl MinIO {
+    /// Sends notifications about `events` (e.g. `put`, `get` or `delete`) of `bucket` to `webhook_url`.
+    ///
+    /// The webhook target is registered through `MINIO_NOTIFY_WEBHOOK_*` environment variables,
+    /// the bucket is created if it doesn't exist yet and then wired to the target with `mc event add`.
+    /// `webhook_url` has to be reachable from inside the container.
+    pub fn with_bucket_notification(
+        mut self,
+        bucket: impl Into<String>,
+        webhook_url: impl Into<String>,
+        events: &[&str],
+    ) -> Self {
+        let target_id = (self.notifications.len() + 1).to_string();
+        self.env_vars.insert(
+            format!("MINIO_NOTIFY_WEBHOOK_ENABLE_{target_id}"),
+            "on".to_owned(),
+        );
+        self.env_vars.insert(
+            format!("MINIO_NOTIFY_WEBHOOK_ENDPOINT_{target_id}"),
+            webhook_url.into(),
+        );
+        self.notifications.push(BucketNotification {
+            bucket: bucket.into(),
+            target_id,
+            events: events.join(","),
+        });
+        self
+    }
+
+    /// Configures a single static key for the built-in KMS (`MINIO_KMS_SECRET_KEY`),
+    /// which enables server-side encryption, e.g. SSE-S3.
+    ///
+    /// `key` is the base64 encoding of 32 random bytes, e.g. generated by `head -c 32 /dev/urandom | base64`.
+    pub fn with_kms_secret_key(
+        mut self,
+        key_name: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Self {
+        self.env_vars.insert(
+            "MINIO_KMS_SECRET_KEY".to_owned(),
+            format!("{}:{}", key_name.into(), key.into()),
+        );
+        self
+    }
+}
+
 #[allow(missing_docs)]
 // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
 #[derive(Debug, Clone)]
@@ -98,13 +158,74 @@ impl Image for MinIO {
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
         &self.cmd
     }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        if self.notifications.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let user = self
+            .env_vars
+            .get("MINIO_ROOT_USER")
+            .map_or("minioadmin", String::as_str);
+        let password = self
+            .env_vars
+            .get("MINIO_ROOT_PASSWORD")
+            .map_or("minioadmin", String::as_str);
+
+        let mut commands = vec![vec![
+            "mc".to_owned(),
+            "alias".to_owned(),
+            "set".to_owned(),
+            MC_ALIAS.to_owned(),
+            "http://localhost:9000".to_owned(),
+            user.to_owned(),
+            password.to_owned(),
+        ]];
+        for notification in &self.notifications {
+            let bucket = format!("{MC_ALIAS}/{}", notification.bucket);
+            commands.push(vec![
+                "mc".to_owned(),
+                "mb".to_owned(),
+                "--ignore-existing".to_owned(),
+                bucket.clone(),
+            ]);
+            commands.push(vec![
+                "mc".to_owned(),
+                "event".to_owned(),
+                "add".to_owned(),
+                bucket,
+                format!("arn:minio:sqs::{}:webhook", notification.target_id),
+                "--event".to_owned(),
+                notification.events.clone(),
+            ]);
+        }
+
+        Ok(commands
+            .into_iter()
+            .map(|cmd| ExecCommand::new(cmd).with_cmd_ready_condition(CmdWaitFor::exit_code(0)))
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
     use aws_config::{meta::region::RegionProviderChain, BehaviorVersion};
-    use aws_sdk_s3::{config::Credentials, Client};
-    use testcontainers::runners::AsyncRunner;
+    use aws_sdk_s3::{
+        config::Credentials, primitives::ByteStream, types::ServerSideEncryption, Client,
+    };
+    use testcontainers::{core::Host, runners::AsyncRunner, ImageExt};
 
     use crate::minio;
 
@@ -137,6 +258,118 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn minio_bucket_notification() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        // stub webhook receiver, forwarding every POST request
+        let listener = TcpListener::bind("0.0.0.0:0")?;
+        let webhook_port = listener.local_addr()?.port();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let request = read_http_request(&mut stream);
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+                if request.starts_with("POST") {
+                    let _ = tx.send(request);
+                }
+            }
+        });
+
+        let bucket_name = "notified-bucket";
+        let node = minio::MinIO::default()
+            .with_bucket_notification(
+                bucket_name,
+                format!("http://host.docker.internal:{webhook_port}/"),
+                &["put"],
+            )
+            .with_host("host.docker.internal", Host::HostGateway)
+            .start()
+            .await?;
+
+        let host_port = node.get_host_port_ipv4(9000).await?;
+        let client = build_s3_client(host_port).await;
+
+        client
+            .put_object()
+            .bucket(bucket_name)
+            .key("test-object")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await
+            .expect("Failed to put test object");
+
+        let notification = loop {
+            let request = rx.recv_timeout(Duration::from_secs(30))?;
+            if request.contains("s3:ObjectCreated:Put") {
+                break request;
+            }
+        };
+        assert!(notification.contains("test-object"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn minio_sse_s3() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = minio::MinIO::default()
+            .with_kms_secret_key("test-key", "OSMM+vkKUTCvQs9YL/CVMIMt43HFhkUpqJxTmGl6rYw=")
+            .start()
+            .await?;
+
+        let host_port = node.get_host_port_ipv4(9000).await?;
+        let client = build_s3_client(host_port).await;
+
+        let bucket_name = "encrypted-bucket";
+        client.create_bucket().bucket(bucket_name).send().await?;
+        client
+            .put_object()
+            .bucket(bucket_name)
+            .key("test-object")
+            .body(ByteStream::from_static(b"secret"))
+            .server_side_encryption(ServerSideEncryption::Aes256)
+            .send()
+            .await?;
+
+        let head = client
+            .head_object()
+            .bucket(bucket_name)
+            .key("test-object")
+            .send()
+            .await?;
+        assert_eq!(
+            head.server_side_encryption(),
+            Some(&ServerSideEncryption::Aes256)
+        );
+        Ok(())
+    }
+
+    fn read_http_request(stream: &mut impl Read) -> String {
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            let n = stream.read(&mut buf).unwrap_or(0);
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let content_length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())
+                            .flatten()
+                    })
+                    .unwrap_or(0);
+                if body.len() >= content_length {
+                    break;
+                }
+            }
+        }
+        String::from_utf8_lossy(&request).into_owned()
+    }
+
     async fn build_s3_client(host_port: u16) -> Client {
         let endpoint_uri = format!("http://127.0.0.1:{host_port}");
         let region_provider = RegionProviderChain::default_provider().or_else("us-east-1");
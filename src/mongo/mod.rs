@@ -1,10 +1,23 @@
+use std::{borrow::Cow, collections::HashMap, time::Duration};
+
 use testcontainers::{
-    core::{CmdWaitFor, ExecCommand, WaitFor},
-    Image,
+    core::{wait::LogWaitStrategy, CmdWaitFor, ContainerPort, ExecCommand, WaitFor},
+    CopyDataSource, CopyToContainer, Image,
 };
 
+use crate::util::retry_until_success;
+
 const NAME: &str = "mongo";
 const TAG: &str = "5.0.6";
+/// Port that the [`MongoDB`] server listens on inside of the container
+///
+/// [`MongoDB`]: https://www.mongodb.com/
+pub const MONGO_PORT: ContainerPort = ContainerPort::Tcp(27017);
+/// Directory of the init scripts of replica sets, which are executed after `rs.initiate()`.
+const REPL_SET_INIT_DIRECTORY: &str = "/testcontainers-init";
+/// Shell loop pinging the server until it answers, used by [`Mongo::with_ping_ready`].
+/// Fails after 600 attempts, i.e. at least a minute.
+const PING_PROBE: &str = "mongosh --quiet --eval 'db.adminCommand({ping:1})' >/dev/null 2>&1";
 
 #[allow(missing_docs)]
 // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
@@ -20,6 +33,9 @@ enum InstanceKind {
 #[derive(Default, Debug, Clone)]
 pub struct Mongo {
     kind: InstanceKind,
+    env_vars: HashMap<String, String>,
+    copy_to_sources: Vec<CopyToContainer>,
+    ping_ready: bool,
 }
 
 impl Mongo {
@@ -28,6 +44,7 @@ impl Mongo {
     pub fn new() -> Self {
         Self {
             kind: InstanceKind::Standalone,
+            ..Default::default()
         }
     }
     // not having docs here is currently allowed to address the missing docs problem one place at a time. Helping us by documenting just one of these places helps other devs tremendously
@@ -35,8 +52,65 @@ impl Mongo {
     pub fn repl_set() -> Self {
         Self {
             kind: InstanceKind::ReplSet,
+            ..Default::default()
         }
     }
+
+    /// Creates a root user with the given name in the `admin` database (`MONGO_INITDB_ROOT_USERNAME`)
+    /// and enables authentication. Its password is set with [`Mongo::with_password`].
+    ///
+    /// Authentication is only supported for standalone instances for now:
+    /// members of a replica set additionally need a shared keyfile to authenticate each other.
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.env_vars
+            .insert("MONGO_INITDB_ROOT_USERNAME".to_owned(), username.into());
+        self
+    }
+
+    /// Sets the password of the root user created by [`Mongo::with_username`] (`MONGO_INITDB_ROOT_PASSWORD`).
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.env_vars
+            .insert("MONGO_INITDB_ROOT_PASSWORD".to_owned(), password.into());
+        self
+    }
+
+    /// Registers a JavaScript file to be executed automatically when the container starts.
+    /// Can be called multiple times to add (not override) scripts, which run in the order of registration.
+    ///
+    /// Standalone instances run the scripts from `/docker-entrypoint-initdb.d` before accepting connections.
+    /// Replica sets run them with `mongosh` once `rs.initiate()` has completed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use testcontainers_modules::mongo::Mongo;
+    /// let mongo_image = Mongo::default().with_init_script(
+    ///     r#"db.getSiblingDB("app").users.insertOne({ name: "admin" });"#
+    ///         .to_string()
+    ///         .into_bytes(),
+    /// );
+    /// ```
+    pub fn with_init_script(mut self, source: impl Into<CopyDataSource>) -> Self {
+        let directory = match self.kind {
+            InstanceKind::Standalone => "/docker-entrypoint-initdb.d",
+            InstanceKind::ReplSet => REPL_SET_INIT_DIRECTORY,
+        };
+        let target = format!("{directory}/init_{i}.js", i = self.copy_to_sources.len());
+        self.copy_to_sources
+            .push(CopyToContainer::new(source.into(), target));
+        self
+    }
+
+    /// Additionally waits for the server to answer a `ping` command after startup,
+    /// by running `mongosh --eval 'db.adminCommand({ping:1})'` until it succeeds,
+    /// failing the start if it doesn't within 600 attempts.
+    ///
+    /// By default, the container is considered ready as soon as it logs "Waiting for connections",
+    /// which can be slightly too early on slow hosts.
+    pub fn with_ping_ready(mut self) -> Self {
+        self.ping_ready = true;
+        self
+    }
 }
 
 impl Image for Mongo {
@@ -48,8 +122,29 @@ impl Image for Mongo {
         TAG
     }
 
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &[MONGO_PORT]
+    }
+
     fn ready_conditions(&self) -> Vec<WaitFor> {
-        vec![WaitFor::message_on_stdout("Waiting for connections")]
+        // to create the root user or to run init scripts, the entrypoint starts
+        // a temporary server first, which logs the same message before it is shut down
+        let runs_initdb = self.env_vars.contains_key("MONGO_INITDB_ROOT_USERNAME")
+            || matches!(self.kind, InstanceKind::Standalone) && !self.copy_to_sources.is_empty();
+        let times = if runs_initdb { 2 } else { 1 };
+        vec![WaitFor::log(
+            LogWaitStrategy::stdout("Waiting for connections").with_times(times),
+        )]
+    }
+
+    fn env_vars(
+        &self,
+    ) -> impl IntoIterator<Item = (impl Into<Cow<'_, str>>, impl Into<Cow<'_, str>>)> {
+        &self.env_vars
+    }
+
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        &self.copy_to_sources
     }
 
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<std::borrow::Cow<'_, str>>> {
@@ -63,28 +158,48 @@ impl Image for Mongo {
         &self,
         _: testcontainers::core::ContainerState,
     ) -> Result<Vec<ExecCommand>, testcontainers::TestcontainersError> {
-        match self.kind {
-            InstanceKind::Standalone => Ok(Default::default()),
-            InstanceKind::ReplSet => Ok(vec![ExecCommand::new(vec![
-                "mongosh".to_string(),
-                "--quiet".to_string(),
-                "--eval".to_string(),
-                "'rs.initiate()'".to_string(),
-            ])
-            .with_cmd_ready_condition(CmdWaitFor::message_on_stdout(
-                "Using a default configuration for the set",
-            ))
-            .with_container_ready_conditions(vec![WaitFor::message_on_stdout(
-                "Rebuilding PrimaryOnlyService due to stepUp",
-            )])]),
+        let mut commands = match self.kind {
+            InstanceKind::Standalone => vec![],
+            InstanceKind::ReplSet => {
+                let initiate = ExecCommand::new(vec![
+                    "mongosh".to_string(),
+                    "--quiet".to_string(),
+                    "--eval".to_string(),
+                    "'rs.initiate()'".to_string(),
+                ])
+                .with_cmd_ready_condition(CmdWaitFor::message_on_stdout(
+                    "Using a default configuration for the set",
+                ))
+                .with_container_ready_conditions(vec![
+                    WaitFor::message_on_stdout("Rebuilding PrimaryOnlyService due to stepUp"),
+                ]);
+                let init_scripts = (0..self.copy_to_sources.len()).map(|i| {
+                    ExecCommand::new(vec![
+                        "mongosh".to_string(),
+                        "--quiet".to_string(),
+                        format!("{REPL_SET_INIT_DIRECTORY}/init_{i}.js"),
+                    ])
+                    .with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+                });
+                std::iter::once(initiate).chain(init_scripts).collect()
+            }
+        };
+        if self.ping_ready {
+            commands.push(retry_until_success(
+                PING_PROBE,
+                Vec::<String>::new(),
+                600,
+                Duration::from_millis(100),
+            ));
         }
+        Ok(commands)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use mongodb::*;
-    use testcontainers::{core::IntoContainerPort, runners::AsyncRunner};
+    use testcontainers::{runners::AsyncRunner, Image};
 
     use crate::mongo;
 
@@ -93,7 +208,7 @@ mod tests {
         let _ = pretty_env_logger::try_init();
         let node = mongo::Mongo::default().start().await?;
         let host_ip = node.get_host().await?;
-        let host_port = node.get_host_port_ipv4(27017.tcp()).await?;
+        let host_port = node.get_host_port_ipv4(mongo::MONGO_PORT).await?;
         let url = format!("mongodb://{host_ip}:{host_port}/");
 
         let client: Client = Client::with_uri_str(&url).await.unwrap();
@@ -118,12 +233,82 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn mongo_ping_ready() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let node = mongo::Mongo::default().with_ping_ready().start().await?;
+        let host_ip = node.get_host().await?;
+        let host_port = node.get_host_port_ipv4(mongo::MONGO_PORT).await?;
+        let url = format!("mongodb://{host_ip}:{host_port}/");
+
+        // no retries: the server has to accept writes right after `start()`
+        let client: Client = Client::with_uri_str(&url).await?;
+        let coll = client
+            .database("some_db")
+            .collection::<bson::Document>("some_coll");
+        coll.insert_one(bson::doc! { "x": 42 }).await?;
+        assert_eq!(coll.count_documents(bson::doc! {}).await?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mongo_init_script() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let script = r#"db.getSiblingDB("seed").items.insertOne({ x: 42 });"#;
+
+        for (image, query) in [
+            (mongo::Mongo::default(), ""),
+            (mongo::Mongo::repl_set(), "?directConnection=true"),
+        ] {
+            let node = image
+                .with_init_script(script.to_string().into_bytes())
+                .start()
+                .await?;
+            let host_ip = node.get_host().await?;
+            let host_port = node.get_host_port_ipv4(mongo::MONGO_PORT).await?;
+            let url = format!("mongodb://{host_ip}:{host_port}/{query}");
+
+            let client: Client = Client::with_uri_str(&url).await?;
+            let coll = client
+                .database("seed")
+                .collection::<bson::Document>("items");
+            let document = coll.find_one(bson::doc! {}).await?.unwrap();
+            assert_eq!(42, document.get_i32("x")?);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mongo_root_authentication() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let node = mongo::Mongo::default()
+            .with_username("root")
+            .with_password("secret")
+            .start()
+            .await?;
+        let host_ip = node.get_host().await?;
+        let host_port = node.get_host_port_ipv4(mongo::MONGO_PORT).await?;
+
+        let url = format!("mongodb://root:secret@{host_ip}:{host_port}/?authSource=admin");
+        let client: Client = Client::with_uri_str(&url).await?;
+        let names = client.list_database_names().await?;
+        assert!(names.iter().any(|name| name == "admin"));
+
+        let url = format!("mongodb://{host_ip}:{host_port}/");
+        let client: Client = Client::with_uri_str(&url).await?;
+        assert!(client.list_database_names().await.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn mongo_repl_set_fetch_document() -> Result<(), Box<dyn std::error::Error + 'static>> {
         let _ = pretty_env_logger::try_init();
         let node = mongo::Mongo::repl_set().start().await?;
         let host_ip = node.get_host().await?;
-        let host_port = node.get_host_port_ipv4(27017).await?;
+        let host_port = node.get_host_port_ipv4(mongo::MONGO_PORT).await?;
         let url = format!("mongodb://{host_ip}:{host_port}/?directConnection=true",);
 
         let client: Client = Client::with_uri_str(url).await?;
@@ -154,4 +339,10 @@ mod tests {
         assert_eq!(42, find_one_result.get_i32("x").unwrap());
         Ok(())
     }
+
+    #[test]
+    fn mongo_expose_ports() {
+        assert_eq!(mongo::Mongo::default().expose_ports(), [mongo::MONGO_PORT]);
+        assert_eq!(mongo::Mongo::repl_set().expose_ports(), [mongo::MONGO_PORT]);
+    }
 }
@@ -1,9 +1,10 @@
 use std::borrow::Cow;
 
-use testcontainers::{core::WaitFor, Image};
+use testcontainers::{core::WaitFor, CopyDataSource, CopyToContainer, Image};
 
 const NAME: &str = "eclipse-mosquitto";
 const TAG: &str = "2.0.18";
+const BRIDGE_CONFIG_PATH: &str = "/mosquitto/config/testcontainers-bridge.conf";
 
 /// Module to work with [`Mosquitto`] inside of tests.
 ///
@@ -23,14 +24,81 @@ const TAG: &str = "2.0.18";
 /// );
 /// ```
 ///
+/// # Bridges
+///
+/// A broker can forward messages to and from other brokers with [`Mosquitto::with_bridge`].
+/// To bridge two containers, start them on the same network and address the remote one by its
+/// container name:
+///
+/// ```rust,ignore
+/// let remote = Mosquitto::default()
+///     .with_network("mqtt")
+///     .with_container_name("remote-broker")
+///     .start()
+///     .await?;
+/// let local = Mosquitto::default()
+///     .with_bridge("to-remote", "remote-broker:1883", &["sensors/#"])
+///     .with_network("mqtt")
+///     .start()
+///     .await?;
+/// ```
+///
 /// [`Mosquitto`]: https://mosquitto.org/
 /// [`Mosquitto docker image`]: https://hub.docker.com/_/eclipse-mosquitto
 #[derive(Debug, Default, Clone)]
 pub struct Mosquitto {
-    /// (remove if there is another variable)
-    /// Field is included to prevent this struct to be a unit struct.
-    /// This allows extending functionality (and thus further variables) without breaking changes
-    _priv: (),
+    bridges: Vec<Bridge>,
+    copy_to_sources: Vec<CopyToContainer>,
+}
+
+/// Bridge connection to another broker.
+#[derive(Debug, Clone)]
+struct Bridge {
+    name: String,
+    address: String,
+    topics: Vec<String>,
+}
+
+impl Mosquitto {
+    /// Bridges the broker to another MQTT broker at `address` (`host:port`).
+    ///
+    /// Messages of the given `topics` (which may contain wildcards) are forwarded in both
+    /// directions. `name` identifies the connection and has to be unique per broker.
+    /// Can be called multiple times to bridge to several brokers.
+    ///
+    /// See the [bridge configuration](https://mosquitto.org/man/mosquitto-conf-5.html) for details.
+    pub fn with_bridge(
+        mut self,
+        name: impl Into<String>,
+        address: impl Into<String>,
+        topics: &[&str],
+    ) -> Self {
+        self.bridges.push(Bridge {
+            name: name.into(),
+            address: address.into(),
+            topics: topics.iter().map(|t| t.to_string()).collect(),
+        });
+        self.copy_to_sources = vec![CopyToContainer::new(
+            CopyDataSource::Data(self.bridge_config().into_bytes()),
+            BRIDGE_CONFIG_PATH,
+        )];
+        self
+    }
+
+    /// Generates a config equivalent to `/mosquitto-no-auth.conf` with `connection` stanzas appended.
+    fn bridge_config(&self) -> String {
+        let mut config = String::from("listener 1883\nallow_anonymous true\n");
+        for bridge in &self.bridges {
+            config.push_str(&format!(
+                "\nconnection {}\naddress {}\n",
+                bridge.name, bridge.address
+            ));
+            for topic in &bridge.topics {
+                config.push_str(&format!("topic {topic} both 0\n"));
+            }
+        }
+        config
+    }
 }
 
 impl Image for Mosquitto {
@@ -50,6 +118,81 @@ impl Image for Mosquitto {
     }
 
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
-        ["mosquitto", "-c", "/mosquitto-no-auth.conf"]
+        let config = if self.bridges.is_empty() {
+            "/mosquitto-no-auth.conf"
+        } else {
+            BRIDGE_CONFIG_PATH
+        };
+        ["mosquitto", "-c", config]
+    }
+
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        &self.copy_to_sources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use testcontainers::{core::ExecCommand, runners::AsyncRunner, ImageExt};
+
+    use super::*;
+
+    #[test]
+    fn bridge_config() {
+        let mosquitto =
+            Mosquitto::default().with_bridge("remote", "remote-broker:1883", &["sensors/#"]);
+        assert_eq!(
+            mosquitto.bridge_config(),
+            "listener 1883\nallow_anonymous true\n\n\
+             connection remote\naddress remote-broker:1883\ntopic sensors/# both 0\n"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "starts two brokers and relies on the bridge connecting within a few seconds"]
+    async fn mosquitto_bridge() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let network = format!("mosquitto-bridge-{}", std::process::id());
+        let remote_name = format!("mosquitto-remote-{}", std::process::id());
+
+        let remote = Mosquitto::default()
+            .with_network(&network)
+            .with_container_name(&remote_name)
+            .start()
+            .await?;
+        let local = Mosquitto::default()
+            .with_bridge("remote", format!("{remote_name}:1883"), &["bridged/#"])
+            .with_network(&network)
+            .start()
+            .await?;
+
+        let mut subscription = remote
+            .exec(ExecCommand::new([
+                "mosquitto_sub",
+                "-t",
+                "bridged/test",
+                "-C",
+                "1",
+                "-W",
+                "30",
+            ]))
+            .await?;
+        // give the subscription and the bridge some time to connect
+        tokio::time::sleep(Duration::from_secs(3)).await;
+
+        local
+            .exec(ExecCommand::new([
+                "mosquitto_pub",
+                "-t",
+                "bridged/test",
+                "-m",
+                "hello",
+            ]))
+            .await?;
+
+        let received = String::from_utf8(subscription.stdout_to_vec().await?)?;
+        assert_eq!(received.trim(), "hello");
+        Ok(())
     }
 }
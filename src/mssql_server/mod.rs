@@ -1,6 +1,15 @@
 use std::{borrow::Cow, collections::HashMap};
 
-use testcontainers::{core::WaitFor, Image};
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
+use testcontainers::{
+    core::{ContainerPort, WaitFor},
+    ContainerAsync, Image, TestcontainersError,
+};
+
+/// Port that the [`MssqlServer`] container has internally
+/// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
+pub const MSSQL_PORT: ContainerPort = ContainerPort::Tcp(1433);
 
 /// [Microsoft SQL Server](https://www.microsoft.com/en-us/sql-server) module
 /// for [testcontainers](https://crates.io/crates/testcontainers).
@@ -21,10 +30,13 @@ use testcontainers::{core::WaitFor, Image};
 /// let ado_connection_string = format!(
 ///    "Server=tcp:{},{};Database=test;User Id=sa;Password=yourStrong(!)Password;TrustServerCertificate=True;",
 ///    mssql_server.get_host().unwrap(),
-///    mssql_server.get_host_port_ipv4(1433).unwrap()
+///    mssql_server.get_host_port_ipv4(mssql_server::MSSQL_PORT).unwrap()
 /// );
 /// ```
 ///
+/// With [tiberius](https://crates.io/crates/tiberius), [`tiberius_config`] returns
+/// a config ready to connect to the container.
+///
 /// # Environment variables
 ///
 /// Refer to the [documentation](https://learn.microsoft.com/en-us/sql/linux/sql-server-linux-configure-environment-variables)
@@ -48,13 +60,45 @@ use testcontainers::{core::WaitFor, Image};
 ///
 /// ## `MSSQL_PID`
 ///
-/// The edition of SQL Server.
+/// The edition of SQL Server, set with [`MssqlServer::with_edition`].
 /// The default value is `Developer`, which will run the container using the Developer Edition.
 #[derive(Debug, Clone)]
 pub struct MssqlServer {
     env_vars: HashMap<String, String>,
 }
 
+/// Edition of SQL Server (`MSSQL_PID`), see [`MssqlServer::with_edition`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MssqlEdition {
+    /// Free edition with all features, licensed for development and testing only.
+    #[default]
+    Developer,
+    /// Free entry-level edition with limited resources.
+    Express,
+    /// Paid Standard edition, requires a license.
+    Standard,
+    /// Paid Enterprise edition, requires a license.
+    Enterprise,
+    /// Paid Enterprise Core edition, requires a license.
+    EnterpriseCore,
+    /// Any other product id, e.g. a product key of the form `#####-#####-#####-#####-#####`.
+    ProductId(String),
+}
+
+impl MssqlEdition {
+    /// Returns the value of `MSSQL_PID` for this edition.
+    pub fn as_str(&self) -> &str {
+        match self {
+            MssqlEdition::Developer => "Developer",
+            MssqlEdition::Express => "Express",
+            MssqlEdition::Standard => "Standard",
+            MssqlEdition::Enterprise => "Enterprise",
+            MssqlEdition::EnterpriseCore => "EnterpriseCore",
+            MssqlEdition::ProductId(pid) => pid,
+        }
+    }
+}
+
 impl MssqlServer {
     const NAME: &'static str = "mcr.microsoft.com/mssql/server";
     const TAG: &'static str = "2022-CU14-ubuntu-22.04";
@@ -69,6 +113,28 @@ impl MssqlServer {
         self
     }
 
+    /// Returns the SA password, set via [`MssqlServer::with_sa_password`] or [`MssqlServer::DEFAULT_SA_PASSWORD`].
+    pub fn sa_password(&self) -> &str {
+        self.env_vars
+            .get("MSSQL_SA_PASSWORD")
+            .map(String::as_str)
+            .unwrap_or(Self::DEFAULT_SA_PASSWORD)
+    }
+
+    /// Returns a [`tiberius::Config`] connecting as `sa` to `host` and `host_port`,
+    /// the host port [`MSSQL_PORT`] is mapped to. The server certificate is trusted,
+    /// since the container uses a self-signed one.
+    ///
+    /// See [`tiberius_config`] and [`tiberius_config_blocking`] to get the config of a running container.
+    pub fn tiberius_config(&self, host: impl ToString, host_port: u16) -> tiberius::Config {
+        let mut config = tiberius::Config::new();
+        config.host(host);
+        config.port(host_port);
+        config.authentication(tiberius::AuthMethod::sql_server("sa", self.sa_password()));
+        config.trust_cert();
+        config
+    }
+
     /// Due to licensing restrictions you are required to explicitly accept an End User License Agreement (EULA) for the MS SQL Server container image.
     /// This is facilitated through the `with_accept_eula` function.
     ///
@@ -77,6 +143,56 @@ impl MssqlServer {
         self.env_vars.insert("ACCEPT_EULA".into(), "Y".into());
         self
     }
+
+    /// Sets the edition of SQL Server as `MSSQL_PID`, [`MssqlEdition::Developer`] by default.
+    ///
+    /// Like the EULA accepted with [`MssqlServer::with_accept_eula`], licensing is up to you:
+    /// by selecting a paid edition (Standard, Enterprise, Enterprise Core or a product key)
+    /// you confirm that you hold a license for it. Developer and Express are free to use for tests.
+    ///
+    /// See the [documentation of `MSSQL_PID`](https://learn.microsoft.com/en-us/sql/linux/sql-server-linux-configure-environment-variables#environment-variables)
+    /// for details.
+    pub fn with_edition(mut self, edition: MssqlEdition) -> Self {
+        self.env_vars
+            .insert("MSSQL_PID".into(), edition.as_str().to_owned());
+        self
+    }
+
+    /// Enables full-text search by setting `MSSQL_ENABLE_FULLTEXT`, so full-text catalogs
+    /// and indexes can be tested.
+    ///
+    /// The official images don't ship the full-text search component (`mssql-server-fts`),
+    /// so this has to be combined with an image that includes it,
+    /// e.g. a custom image selected via [`ImageExt::with_name`] and [`ImageExt::with_tag`].
+    ///
+    /// [`ImageExt::with_name`]: testcontainers::ImageExt::with_name
+    /// [`ImageExt::with_tag`]: testcontainers::ImageExt::with_tag
+    pub fn with_fulltext(mut self) -> Self {
+        self.env_vars
+            .insert("MSSQL_ENABLE_FULLTEXT".into(), "Y".into());
+        self
+    }
+}
+
+/// Returns the [`MssqlServer::tiberius_config`] of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`MSSQL_PORT`].
+pub async fn tiberius_config(
+    container: &ContainerAsync<MssqlServer>,
+) -> Result<tiberius::Config, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(MSSQL_PORT).await?;
+    Ok(container.image().tiberius_config(host, port))
+}
+
+/// Returns the [`MssqlServer::tiberius_config`] of a container started with the `SyncRunner`,
+/// using the host port mapped to [`MSSQL_PORT`].
+#[cfg(feature = "blocking")]
+pub fn tiberius_config_blocking(
+    container: &Container<MssqlServer>,
+) -> Result<tiberius::Config, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(MSSQL_PORT)?;
+    Ok(container.image().tiberius_config(host, port))
 }
 
 impl Default for MssqlServer {
@@ -86,7 +202,10 @@ impl Default for MssqlServer {
             "MSSQL_SA_PASSWORD".to_owned(),
             Self::DEFAULT_SA_PASSWORD.to_owned(),
         );
-        env_vars.insert("MSSQL_PID".to_owned(), "Developer".to_owned());
+        env_vars.insert(
+            "MSSQL_PID".to_owned(),
+            MssqlEdition::default().as_str().to_owned(),
+        );
 
         Self { env_vars }
     }
@@ -101,6 +220,10 @@ impl Image for MssqlServer {
         Self::TAG
     }
 
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &[MSSQL_PORT]
+    }
+
     fn ready_conditions(&self) -> Vec<WaitFor> {
         // Wait until all system databases are recovered
         vec![
@@ -121,7 +244,7 @@ mod tests {
     use std::error;
 
     use testcontainers::runners::AsyncRunner;
-    use tiberius::{AuthMethod, Client, Config};
+    use tiberius::{Client, Config};
     use tokio::net::TcpStream;
     use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
 
@@ -130,11 +253,7 @@ mod tests {
     #[tokio::test]
     async fn one_plus_one() -> Result<(), Box<dyn error::Error>> {
         let container = MssqlServer::default().with_accept_eula().start().await?;
-        let config = new_config(
-            container.get_host().await?,
-            container.get_host_port_ipv4(1433).await?,
-            MssqlServer::DEFAULT_SA_PASSWORD,
-        );
+        let config = tiberius_config(&container).await?;
         let mut client = get_mssql_client(config).await?;
 
         let stream = client.query("SELECT 1 + 1", &[]).await?;
@@ -145,17 +264,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn tiberius_config_uses_sa_password() {
+        let image = MssqlServer::default();
+        assert_eq!(image.sa_password(), MssqlServer::DEFAULT_SA_PASSWORD);
+        let config = image.tiberius_config("127.0.0.1", 11433);
+        assert_eq!(config.get_addr(), "127.0.0.1:11433");
+
+        let image = image.with_sa_password("yourStrongPassword123!");
+        assert_eq!(image.sa_password(), "yourStrongPassword123!");
+    }
+
+    #[tokio::test]
+    async fn express_edition() -> Result<(), Box<dyn error::Error>> {
+        let image = MssqlServer::default()
+            .with_accept_eula()
+            .with_edition(MssqlEdition::Express);
+        assert_eq!(image.env_vars["MSSQL_PID"], "Express");
+
+        let container = image.start().await?;
+        let config = tiberius_config(&container).await?;
+        let mut client = get_mssql_client(config).await?;
+
+        let stream = client
+            .query(
+                "SELECT CAST(SERVERPROPERTY('Edition') AS NVARCHAR(128))",
+                &[],
+            )
+            .await?;
+        let row = stream.into_row().await?.unwrap();
+        let edition = row.get::<&str, _>(0).unwrap();
+        assert!(edition.starts_with("Express Edition"), "{edition}");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn custom_sa_password() -> Result<(), Box<dyn error::Error>> {
         let image = MssqlServer::default()
             .with_accept_eula()
             .with_sa_password("yourStrongPassword123!");
         let container = image.start().await?;
-        let config = new_config(
-            container.get_host().await?,
-            container.get_host_port_ipv4(1433).await?,
-            "yourStrongPassword123!",
-        );
+        let config = tiberius_config(&container).await?;
         let mut client = get_mssql_client(config).await?;
 
         let stream = client.query("SELECT 1 + 1", &[]).await?;
@@ -166,6 +316,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn fulltext_env_var() {
+        let image = MssqlServer::default().with_fulltext();
+        assert_eq!(image.env_vars["MSSQL_ENABLE_FULLTEXT"], "Y");
+    }
+
+    /// The official images don't include `mssql-server-fts`, so this needs an image built with it,
+    /// e.g. `FROM mcr.microsoft.com/mssql/server:2022-latest` installing the `mssql-server-fts`
+    /// package, selected with `ImageExt::with_name` and `ImageExt::with_tag`.
+    #[tokio::test]
+    #[ignore = "requires an image with the full-text search component (mssql-server-fts) installed"]
+    async fn fulltext_search() -> Result<(), Box<dyn error::Error>> {
+        let container = MssqlServer::default()
+            .with_accept_eula()
+            .with_fulltext()
+            .start()
+            .await?;
+        let config = tiberius_config(&container).await?;
+        let mut client = get_mssql_client(config).await?;
+
+        client
+            .simple_query(
+                "CREATE TABLE documents (id INT NOT NULL CONSTRAINT pk_documents PRIMARY KEY, body NVARCHAR(200));
+                 INSERT INTO documents VALUES (1, 'testcontainers for rust'), (2, 'something else');
+                 CREATE FULLTEXT CATALOG documents_catalog AS DEFAULT;
+                 CREATE FULLTEXT INDEX ON documents(body) KEY INDEX pk_documents WITH CHANGE_TRACKING AUTO;",
+            )
+            .await?
+            .into_results()
+            .await?;
+
+        // the index is populated asynchronously
+        let mut ids = vec![];
+        for _ in 0..30 {
+            ids = client
+                .query(
+                    "SELECT id FROM documents WHERE CONTAINS(body, 'testcontainers')",
+                    &[],
+                )
+                .await?
+                .into_first_result()
+                .await?
+                .iter()
+                .filter_map(|row| row.get::<i32, _>(0))
+                .collect::<Vec<_>>();
+            if !ids.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+        assert_eq!(ids, vec![1]);
+
+        Ok(())
+    }
+
     async fn get_mssql_client(
         config: Config,
     ) -> Result<Client<Compat<TcpStream>>, Box<dyn error::Error>> {
@@ -177,13 +382,8 @@ mod tests {
         Ok(client)
     }
 
-    fn new_config(host: impl ToString, port: u16, password: &str) -> Config {
-        let mut config = Config::new();
-        config.host(host);
-        config.port(port);
-        config.authentication(AuthMethod::sql_server("sa", password));
-        config.trust_cert();
-
-        config
+    #[test]
+    fn mssql_expose_ports() {
+        assert_eq!(MssqlServer::default().expose_ports(), [MSSQL_PORT]);
     }
 }
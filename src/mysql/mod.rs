@@ -1,13 +1,22 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
-use testcontainers::{core::WaitFor, CopyDataSource, CopyToContainer, Image};
+use testcontainers::{
+    core::{ContainerPort, WaitFor},
+    CopyDataSource, CopyToContainer, Image,
+};
 
 const NAME: &str = "mysql";
 const TAG: &str = "8.1";
+/// Port that the [`MySQL`] server listens on inside of the container
+///
+/// [`MySQL`]: https://www.mysql.com/
+pub const MYSQL_PORT: ContainerPort = ContainerPort::Tcp(3306);
 
 /// Module to work with [`MySQL`] inside of tests.
 ///
 /// Starts an instance of MySQL with no password set for the root user and a default database named `test` created.
+/// Credentials and the database can be changed with [`Mysql::with_database`], [`Mysql::with_user`],
+/// [`Mysql::with_password`] and [`Mysql::with_root_password`].
 ///
 /// This module is based on the officlal [`MySQL docker image`].
 ///
@@ -19,17 +28,63 @@ const TAG: &str = "8.1";
 /// let mysql_url = format!(
 ///     "mysql://{}:{}/test",
 ///     mysql_instance.get_host().unwrap(),
-///     mysql_instance.get_host_port_ipv4(3306).unwrap()
+///     mysql_instance
+///         .get_host_port_ipv4(mysql::MYSQL_PORT)
+///         .unwrap()
 /// );
 /// ```
 ///
 /// [`MySQL`]: https://www.mysql.com/
 /// [`MySQL docker image`]: https://hub.docker.com/_/mysql
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Mysql {
+    env_vars: HashMap<String, String>,
+    allow_empty_password: Option<bool>,
     copy_to_sources: Vec<CopyToContainer>,
 }
+
 impl Mysql {
+    /// Sets the name of the database created on startup (`MYSQL_DATABASE`).
+    /// Default: `test`
+    pub fn with_database(mut self, database: &str) -> Self {
+        self.env_vars
+            .insert("MYSQL_DATABASE".to_owned(), database.to_owned());
+        self
+    }
+
+    /// Creates a non-root user (`MYSQL_USER`), which is granted all privileges on the database.
+    ///
+    /// Its password is set with [`Mysql::with_password`].
+    /// Once a user is set, the root user doesn't get an empty password anymore: it gets a random one
+    /// (`MYSQL_RANDOM_ROOT_PASSWORD`) unless [`Mysql::with_root_password`] is set,
+    /// or [`Mysql::with_empty_root_password`] to keep the root user without a password.
+    pub fn with_user(mut self, user: &str) -> Self {
+        self.env_vars
+            .insert("MYSQL_USER".to_owned(), user.to_owned());
+        self
+    }
+
+    /// Sets the password of the user set with [`Mysql::with_user`] (`MYSQL_PASSWORD`).
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.env_vars
+            .insert("MYSQL_PASSWORD".to_owned(), password.to_owned());
+        self
+    }
+
+    /// Sets the password of the root user (`MYSQL_ROOT_PASSWORD`).
+    pub fn with_root_password(mut self, root_password: &str) -> Self {
+        self.env_vars
+            .insert("MYSQL_ROOT_PASSWORD".to_owned(), root_password.to_owned());
+        self
+    }
+
+    /// Explicitly allows the root user to have no password (`MYSQL_ALLOW_EMPTY_PASSWORD`),
+    /// which is the default unless [`Mysql::with_user`] or [`Mysql::with_root_password`] is set.
+    pub fn with_empty_root_password(mut self) -> Self {
+        self.allow_empty_password = Some(true);
+        self
+    }
+
     /// Registers sql to be executed automatically when the container starts.
     /// Can be called multiple times to add (not override) scripts.
     ///
@@ -60,6 +115,19 @@ impl Mysql {
     }
 }
 
+impl Default for Mysql {
+    fn default() -> Self {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("MYSQL_DATABASE".to_owned(), "test".to_owned());
+
+        Self {
+            env_vars,
+            allow_empty_password: None,
+            copy_to_sources: Vec::new(),
+        }
+    }
+}
+
 impl Image for Mysql {
     fn name(&self) -> &str {
         NAME
@@ -69,6 +137,10 @@ impl Image for Mysql {
         TAG
     }
 
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &[MYSQL_PORT]
+    }
+
     fn ready_conditions(&self) -> Vec<WaitFor> {
         vec![
             WaitFor::message_on_stderr("X Plugin ready for connections. Bind-address"),
@@ -79,11 +151,21 @@ impl Image for Mysql {
     fn env_vars(
         &self,
     ) -> impl IntoIterator<Item = (impl Into<Cow<'_, str>>, impl Into<Cow<'_, str>>)> {
-        [
-            ("MYSQL_DATABASE", "test"),
-            ("MYSQL_ALLOW_EMPTY_PASSWORD", "yes"),
-        ]
+        let mut vars = self.env_vars.clone();
+        let has_user = vars.contains_key("MYSQL_USER");
+        let has_root_password = vars.contains_key("MYSQL_ROOT_PASSWORD");
+        let allow_empty_password = self
+            .allow_empty_password
+            .unwrap_or(!has_user && !has_root_password);
+        if allow_empty_password {
+            vars.insert("MYSQL_ALLOW_EMPTY_PASSWORD".to_owned(), "yes".to_owned());
+        } else if !has_root_password {
+            // the server refuses to start without any of the root password options
+            vars.insert("MYSQL_RANDOM_ROOT_PASSWORD".to_owned(), "yes".to_owned());
+        }
+        vars
     }
+
     fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
         &self.copy_to_sources
     }
@@ -91,11 +173,13 @@ impl Image for Mysql {
 
 #[cfg(test)]
 mod tests {
+    use std::{borrow::Cow, collections::HashMap};
+
     use mysql::prelude::Queryable;
-    use testcontainers::core::IntoContainerPort;
+    use testcontainers::Image;
 
     use crate::{
-        mysql::Mysql as MysqlImage,
+        mysql::{Mysql as MysqlImage, MYSQL_PORT},
         testcontainers::{runners::SyncRunner, ImageExt},
     };
 
@@ -112,7 +196,7 @@ mod tests {
         let connection_string = &format!(
             "mysql://root@{}:{}/test",
             node.get_host()?,
-            node.get_host_port_ipv4(3306.tcp())?
+            node.get_host_port_ipv4(MYSQL_PORT)?
         );
         let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
 
@@ -124,6 +208,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn mysql_env_vars() {
+        let env_vars = |mysql: &MysqlImage| -> HashMap<String, String> {
+            mysql
+                .env_vars()
+                .into_iter()
+                .map(|(k, v)| {
+                    let (k, v): (Cow<'_, str>, Cow<'_, str>) = (k.into(), v.into());
+                    (k.into_owned(), v.into_owned())
+                })
+                .collect()
+        };
+
+        let defaults = env_vars(&MysqlImage::default());
+        assert_eq!(defaults["MYSQL_DATABASE"], "test");
+        assert_eq!(defaults["MYSQL_ALLOW_EMPTY_PASSWORD"], "yes");
+
+        let with_user = env_vars(&MysqlImage::default().with_user("app").with_password("p"));
+        assert!(!with_user.contains_key("MYSQL_ALLOW_EMPTY_PASSWORD"));
+        assert_eq!(with_user["MYSQL_RANDOM_ROOT_PASSWORD"], "yes");
+
+        let with_root_password = env_vars(
+            &MysqlImage::default()
+                .with_user("app")
+                .with_root_password("secret"),
+        );
+        assert!(!with_root_password.contains_key("MYSQL_ALLOW_EMPTY_PASSWORD"));
+        assert!(!with_root_password.contains_key("MYSQL_RANDOM_ROOT_PASSWORD"));
+
+        let opted_in = env_vars(
+            &MysqlImage::default()
+                .with_user("app")
+                .with_empty_root_password(),
+        );
+        assert_eq!(opted_in["MYSQL_ALLOW_EMPTY_PASSWORD"], "yes");
+        assert!(!opted_in.contains_key("MYSQL_RANDOM_ROOT_PASSWORD"));
+    }
+
+    #[test]
+    fn mysql_custom_credentials() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = MysqlImage::default()
+            .with_database("app_db")
+            .with_user("app")
+            .with_password("app-password")
+            .with_root_password("root-password")
+            .start()?;
+
+        let host = node.get_host()?;
+        let port = node.get_host_port_ipv4(MYSQL_PORT)?;
+        let connection_string = &format!("mysql://app:app-password@{host}:{port}/app_db");
+        let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
+
+        let row: Option<(String, String)> = conn
+            .query_first("SELECT SUBSTRING_INDEX(CURRENT_USER(), '@', 1), DATABASE()")
+            .unwrap();
+        assert_eq!(row, Some(("app".to_owned(), "app_db".to_owned())));
+
+        let root_connection_string = &format!("mysql://root:root-password@{host}:{port}/mysql");
+        assert!(mysql::Conn::new(mysql::Opts::from_url(root_connection_string).unwrap()).is_ok());
+        Ok(())
+    }
+
     #[test]
     fn mysql_one_plus_one() -> Result<(), Box<dyn std::error::Error + 'static>> {
         let mysql_image = MysqlImage::default();
@@ -132,7 +278,7 @@ mod tests {
         let connection_string = &format!(
             "mysql://root@{}:{}/mysql",
             node.get_host()?,
-            node.get_host_port_ipv4(3306)?
+            node.get_host_port_ipv4(MYSQL_PORT)?
         );
         let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
 
@@ -152,7 +298,7 @@ mod tests {
         let connection_string = &format!(
             "mysql://root@{}:{}/mysql",
             node.get_host()?,
-            node.get_host_port_ipv4(3306)?
+            node.get_host_port_ipv4(MYSQL_PORT)?
         );
 
         let mut conn = mysql::Conn::new(mysql::Opts::from_url(connection_string).unwrap()).unwrap();
@@ -160,4 +306,9 @@ mod tests {
         assert_eq!(first_row, Some(String::from("8.0.34")));
         Ok(())
     }
+
+    #[test]
+    fn mysql_expose_ports() {
+        assert_eq!(MysqlImage::default().expose_ports(), [MYSQL_PORT]);
+    }
 }
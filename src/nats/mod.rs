@@ -1,9 +1,19 @@
 use std::borrow::Cow;
 
-use testcontainers::{core::WaitFor, Image};
+use testcontainers::{
+    core::{wait::HttpWaitStrategy, ContainerPort, WaitFor},
+    CopyDataSource, CopyToContainer, Image,
+};
 
 const NAME: &str = "nats";
 const TAG: &str = "2.10.14";
+/// Port clients connect to inside of the container
+pub const NATS_CLIENT_PORT: ContainerPort = ContainerPort::Tcp(4222);
+/// Default port of the monitoring endpoint, see [`NatsServerCmd::with_monitoring`]
+pub const NATS_MONITORING_PORT: ContainerPort = ContainerPort::Tcp(8222);
+/// Path of the generated configuration file holding the JetStream limits,
+/// see [`Nats::with_jetstream_max_memory`] and [`Nats::with_jetstream_max_file`]
+const JETSTREAM_CONFIG_PATH: &str = "/etc/nats/jetstream.conf";
 
 /// Nats image for [testcontainers](https://crates.io/crates/testcontainers).
 ///
@@ -11,6 +21,69 @@ const TAG: &str = "2.10.14";
 #[derive(Debug, Default, Clone)]
 pub struct Nats {
     cmd: NatsServerCmd,
+    exposed_ports: Vec<ContainerPort>,
+    max_memory_store: Option<u64>,
+    max_file_store: Option<u64>,
+    jetstream_config: Option<CopyToContainer>,
+}
+
+impl Nats {
+    /// Starts the server with the arguments of `cmd`.
+    ///
+    /// Unlike [`ImageExt::with_cmd`](testcontainers::ImageExt::with_cmd), this lets the image know
+    /// the command: if [`NatsServerCmd::with_monitoring`] is set, the `/healthz` route is awaited on
+    /// startup, and both [`NATS_CLIENT_PORT`] and the monitoring port are exposed.
+    pub fn with_server_cmd(mut self, cmd: NatsServerCmd) -> Self {
+        self.exposed_ports = match cmd.monitoring_port {
+            Some(port) => vec![NATS_CLIENT_PORT, ContainerPort::Tcp(port)],
+            None => vec![],
+        };
+        self.cmd = cmd;
+        self
+    }
+
+    /// Limits the memory JetStream may use for streams to `bytes`, enabling JetStream.
+    ///
+    /// The server has no command line flag for it, so the limit is written into a configuration
+    /// file, which is copied into the container and loaded with `-c`. Pass the arguments with
+    /// [`Nats::with_server_cmd`] rather than [`ImageExt::with_cmd`](testcontainers::ImageExt::with_cmd),
+    /// which replaces the `-c` flag along with the rest of the command.
+    ///
+    /// See: https://docs.nats.io/running-a-nats-service/configuration#jetstream
+    pub fn with_jetstream_max_memory(mut self, bytes: u64) -> Self {
+        self.max_memory_store = Some(bytes);
+        self.jetstream_config = Some(CopyToContainer::new(
+            CopyDataSource::Data(self.jetstream_config().into_bytes()),
+            JETSTREAM_CONFIG_PATH,
+        ));
+        self
+    }
+
+    /// Limits the disk space JetStream may use for streams to `bytes`, enabling JetStream.
+    ///
+    /// Like [`Nats::with_jetstream_max_memory`], the limit is written into a configuration file,
+    /// so the arguments have to be passed with [`Nats::with_server_cmd`].
+    pub fn with_jetstream_max_file(mut self, bytes: u64) -> Self {
+        self.max_file_store = Some(bytes);
+        self.jetstream_config = Some(CopyToContainer::new(
+            CopyDataSource::Data(self.jetstream_config().into_bytes()),
+            JETSTREAM_CONFIG_PATH,
+        ));
+        self
+    }
+
+    /// Returns the `jetstream` block of the configuration file holding the limits.
+    fn jetstream_config(&self) -> String {
+        let mut config = String::from("jetstream {\n");
+        if let Some(max_memory_store) = self.max_memory_store {
+            config.push_str(&format!("  max_memory_store: {max_memory_store}\n"));
+        }
+        if let Some(max_file_store) = self.max_file_store {
+            config.push_str(&format!("  max_file_store: {max_file_store}\n"));
+        }
+        config.push_str("}\n");
+        config
+    }
 }
 
 #[allow(missing_docs)]
@@ -19,8 +92,11 @@ pub struct Nats {
 pub struct NatsServerCmd {
     user: Option<String>,
     pass: Option<String>,
+    server_name: Option<String>,
+    monitoring_port: Option<u16>,
 
     jetstream: Option<bool>,
+    store_dir: Option<String>,
 }
 
 impl NatsServerCmd {
@@ -38,6 +114,25 @@ impl NatsServerCmd {
         self
     }
 
+    /// Sets the name of the server with `--name`, e.g. to tell servers of a JetStream cluster apart.
+    pub fn with_server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    /// Enables the HTTP monitoring endpoint on `port` with `-m`, usually [`NATS_MONITORING_PORT`].
+    ///
+    /// When passed to [`Nats::with_server_cmd`], the image waits for the `/healthz` route
+    /// to report the server as ready on startup. When passed to
+    /// [`ImageExt::with_cmd`](testcontainers::ImageExt::with_cmd), the endpoint is enabled as well,
+    /// but the image waits for the log messages of the server as usual.
+    ///
+    /// See: https://docs.nats.io/running-a-nats-service/nats_admin/monitoring
+    pub fn with_monitoring(mut self, port: u16) -> Self {
+        self.monitoring_port = Some(port);
+        self
+    }
+
     /// Enable JetStream in the Nats server to use the built-in persistence
     /// features of NATS.
     ///
@@ -53,6 +148,14 @@ impl NatsServerCmd {
         self.jetstream = Some(true);
         self
     }
+
+    /// Sets the directory JetStream stores its data in with `--store_dir`, enabling JetStream.
+    ///
+    /// Combine it with a mount to keep the data of streams across containers.
+    pub fn with_store_dir(mut self, store_dir: impl Into<String>) -> Self {
+        self.store_dir = Some(store_dir.into());
+        self.with_jetstream()
+    }
 }
 
 impl IntoIterator for &NatsServerCmd {
@@ -70,12 +173,23 @@ impl IntoIterator for &NatsServerCmd {
             args.push("--pass".to_owned());
             args.push(pass.to_owned())
         }
-
+        if let Some(ref server_name) = self.server_name {
+            args.push("--name".to_owned());
+            args.push(server_name.to_owned())
+        }
         if let Some(ref jetstream) = self.jetstream {
             if *jetstream {
                 args.push("--jetstream".to_owned());
+                if let Some(ref store_dir) = self.store_dir {
+                    args.push("--store_dir".to_owned());
+                    args.push(store_dir.to_owned())
+                }
             }
         }
+        if let Some(monitoring_port) = self.monitoring_port {
+            args.push("-m".to_owned());
+            args.push(monitoring_port.to_string());
+        }
 
         args.into_iter()
     }
@@ -91,6 +205,13 @@ impl Image for Nats {
     }
 
     fn ready_conditions(&self) -> Vec<WaitFor> {
+        if let Some(monitoring_port) = self.cmd.monitoring_port {
+            return vec![WaitFor::http(
+                HttpWaitStrategy::new("/healthz")
+                    .with_port(ContainerPort::Tcp(monitoring_port))
+                    .with_expected_status_code(200_u16),
+            )];
+        }
         vec![
             WaitFor::message_on_stderr("Listening for client connections on 0.0.0.0:4222"),
             WaitFor::message_on_stderr("Server is ready"),
@@ -98,7 +219,21 @@ impl Image for Nats {
     }
 
     fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
-        &self.cmd
+        let mut args = self.cmd.into_iter().collect::<Vec<_>>();
+        // only loaded here, as the file is only copied into the container by the image
+        if self.jetstream_config.is_some() {
+            args.push("-c".to_owned());
+            args.push(JETSTREAM_CONFIG_PATH.to_owned());
+        }
+        args
+    }
+
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &self.exposed_ports
+    }
+
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        &self.jetstream_config
     }
 }
 
@@ -108,9 +243,9 @@ mod tests {
 
     use async_nats::jetstream::{self, consumer::PushConsumer};
     use futures::StreamExt;
-    use testcontainers::{runners::AsyncRunner, ImageExt};
+    use testcontainers::{runners::AsyncRunner, Image, ImageExt};
 
-    use crate::nats::{Nats, NatsServerCmd};
+    use crate::nats::{Nats, NatsServerCmd, NATS_CLIENT_PORT, NATS_MONITORING_PORT};
 
     #[test]
     fn set_user() {
@@ -131,6 +266,131 @@ mod tests {
         let nats_cmd_args = NatsServerCmd::default().with_jetstream();
         assert_eq!(nats_cmd_args.jetstream, Some(true));
         let _image_with_cmd = Nats::default().with_cmd(&nats_cmd_args);
+
+        let nats_cmd_args = NatsServerCmd::default()
+            .with_store_dir("/data/jetstream")
+            .with_server_name("nats-1")
+            .with_user("custom_user");
+        assert_eq!(nats_cmd_args.jetstream, Some(true));
+        assert_eq!(
+            nats_cmd_args.into_iter().collect::<Vec<_>>(),
+            vec![
+                "--user",
+                "custom_user",
+                "--name",
+                "nats-1",
+                "--jetstream",
+                "--store_dir",
+                "/data/jetstream",
+            ]
+        );
+
+        let nats_cmd_args = NatsServerCmd::default()
+            .with_store_dir("/data/jetstream")
+            .with_monitoring(8222);
+        let image = Nats::default()
+            .with_jetstream_max_file(1024 * 1024 * 1024)
+            .with_server_cmd(nats_cmd_args)
+            .with_jetstream_max_memory(64 * 1024 * 1024);
+        assert_eq!(image.copy_to_sources().into_iter().count(), 1);
+        assert_eq!(
+            image.jetstream_config(),
+            "jetstream {\n  max_memory_store: 67108864\n  max_file_store: 1073741824\n}\n"
+        );
+        assert_eq!(
+            image.cmd().into_iter().map(Into::into).collect::<Vec<_>>(),
+            vec![
+                "--jetstream",
+                "--store_dir",
+                "/data/jetstream",
+                "-m",
+                "8222",
+                "-c",
+                "/etc/nats/jetstream.conf",
+            ]
+        );
+
+        // the configuration file is only copied and loaded if a limit is set
+        let nats_cmd_args = NatsServerCmd::default().with_store_dir("/data/jetstream");
+        let image = Nats::default().with_server_cmd(nats_cmd_args);
+        assert_eq!(image.copy_to_sources().into_iter().count(), 0);
+        assert_eq!(
+            image.cmd().into_iter().map(Into::into).collect::<Vec<_>>(),
+            vec!["--jetstream", "--store_dir", "/data/jetstream"]
+        );
+    }
+
+    #[test]
+    fn monitoring_exposes_client_port() {
+        assert!(Nats::default().expose_ports().is_empty());
+
+        let nats_cmd_args = NatsServerCmd::default().with_monitoring(8222);
+        assert_eq!(
+            nats_cmd_args.into_iter().collect::<Vec<_>>(),
+            vec!["-m", "8222"]
+        );
+        let image = Nats::default().with_server_cmd(nats_cmd_args);
+        assert_eq!(
+            image.expose_ports(),
+            [NATS_CLIENT_PORT, NATS_MONITORING_PORT]
+        );
+        assert_eq!(
+            image.cmd().into_iter().map(Into::into).collect::<Vec<_>>(),
+            vec!["-m", "8222"]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_works_with_monitoring() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let nats_cmd = NatsServerCmd::default().with_monitoring(8222);
+        let container = Nats::default().with_server_cmd(nats_cmd).start().await?;
+
+        let host = container.get_host().await?;
+        let host_port = container.get_host_port_ipv4(NATS_MONITORING_PORT).await?;
+        let healthz = reqwest::get(format!("http://{host}:{host_port}/healthz"))
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(healthz["status"], "ok");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_works_with_monitoring_via_with_cmd(
+    ) -> Result<(), Box<dyn std::error::Error + 'static>> {
+        // the image doesn't know the command, so it waits for the log messages instead
+        let nats_cmd = NatsServerCmd::default().with_monitoring(8222);
+        let container = Nats::default().with_cmd(&nats_cmd).start().await?;
+
+        let host = container.get_host().await?;
+        let host_port = container.get_host_port_ipv4(NATS_MONITORING_PORT).await?;
+        let healthz = reqwest::get(format!("http://{host}:{host_port}/healthz"))
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(healthz["status"], "ok");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_works_with_jetstream_limits() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let nats_cmd = NatsServerCmd::default().with_monitoring(8222);
+        let container = Nats::default()
+            .with_server_cmd(nats_cmd)
+            .with_jetstream_max_memory(64 * 1024 * 1024)
+            .with_jetstream_max_file(256 * 1024 * 1024)
+            .start()
+            .await?;
+
+        let host = container.get_host().await?;
+        let host_port = container.get_host_port_ipv4(NATS_MONITORING_PORT).await?;
+        let jsz = reqwest::get(format!("http://{host}:{host_port}/jsz"))
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        assert_eq!(jsz["config"]["max_memory"], 64 * 1024 * 1024);
+        assert_eq!(jsz["config"]["max_storage"], 256 * 1024 * 1024);
+        Ok(())
     }
 
     #[tokio::test]
@@ -1,14 +1,31 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
     sync::RwLock,
 };
 
 use testcontainers::{
-    core::{ContainerState, IntoContainerPort, WaitFor},
-    ContainerRequest, Image, TestcontainersError,
+    core::{ContainerPort, ContainerState, Mount, WaitFor},
+    ContainerRequest, CopyDataSource, CopyToContainer, Image, TestcontainersError,
 };
 
+use crate::{tls::GeneratedCert, ModuleConfigError};
+
+/// Port of the [`Bolt`] protocol that the Neo4j container has internally
+/// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
+///
+/// [`Bolt`]: https://neo4j.com/docs/bolt/current/
+pub const BOLT_PORT: ContainerPort = ContainerPort::Tcp(7687);
+/// Port of the HTTP API and the browser that the Neo4j container has internally
+/// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
+pub const HTTP_PORT: ContainerPort = ContainerPort::Tcp(7474);
+
+const BOLT_TLS_DIRECTORY: &str = "/ssl/bolt";
+const IMPORT_DIRECTORY: &str = "/var/lib/neo4j/import";
+const LOGS_DIRECTORY: &str = "/logs";
+const LICENSES_DIRECTORY: &str = "/licenses";
+
 /// Available Neo4j plugins.
 /// See [Neo4j operations manual](https://neo4j.com/docs/operations-manual/current/docker/operations/#docker-neo4j-plugins) for more information.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -114,6 +131,12 @@ impl std::fmt::Display for Neo4jLabsPlugin {
 /// The used user can be retrieved with the `user` method.
 /// The used password can be retrieved with the `pass` method.
 ///
+/// # Ports
+///
+/// Bolt is exposed on [`BOLT_PORT`] and the HTTP API on [`HTTP_PORT`].
+/// The mapped host ports can be retrieved with `get_host_port_ipv4` of the container,
+/// or with the `bolt_port_ipv4` and `http_port_ipv4` methods of the image.
+///
 /// # Environment variables
 ///
 /// The following environment variables are supported:
@@ -127,12 +150,36 @@ impl std::fmt::Display for Neo4jLabsPlugin {
 /// The method `with_neo4j_labs_plugin` can be used to define them.
 ///
 /// Supported plugins are APOC, APOC Core, Bloom, Streams, Graph Data Science, and Neo Semantics.
+///
+/// # Bolt TLS
+///
+/// The method `with_bolt_tls` requires encrypted Bolt connections using a generated certificate.
+/// The CA of that certificate can be retrieved with the `bolt_tls_ca` method.
+///
+/// # Cluster mode
+///
+/// The method `with_cluster_mode` configures the container as one primary ("core") server
+/// of a cluster. This requires the enterprise edition of Neo4j, see [`Neo4j::with_cluster_mode`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Neo4j {
     version: Value,
     user: Option<Value>,
     pass: Option<Value>,
     plugins: BTreeSet<Neo4jLabsPlugin>,
+    bolt_tls: Option<GeneratedCert>,
+    cluster: Option<Neo4jCluster>,
+    import_dir: Option<String>,
+    logs_dir: Option<String>,
+    licenses_dir: Option<String>,
+    query_logging: bool,
+    config: BTreeMap<String, String>,
+}
+
+/// Configuration of a cluster member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Neo4jCluster {
+    advertised_address: String,
+    members: Vec<String>,
 }
 
 impl Neo4j {
@@ -148,6 +195,13 @@ impl Neo4j {
             user: Some(Cow::Borrowed(Self::DEFAULT_USER)),
             pass: Some(Cow::Borrowed(Self::DEFAULT_PASS)),
             plugins: BTreeSet::new(),
+            bolt_tls: None,
+            cluster: None,
+            import_dir: None,
+            logs_dir: None,
+            licenses_dir: None,
+            query_logging: false,
+            config: BTreeMap::new(),
         }
     }
 
@@ -188,6 +242,155 @@ impl Neo4j {
         self.plugins.extend(plugins.iter().cloned());
         self
     }
+
+    /// Require TLS encryption for Bolt connections.
+    ///
+    /// A self-signed CA and a server certificate for `localhost`, `127.0.0.1` and `::1`
+    /// are generated and installed as the `bolt` SSL policy, and `server.bolt.tls_level`
+    /// is set to `REQUIRED`, so clients have to connect with the `neo4j+s://` or `bolt+s://` scheme.
+    /// The CA to trust can be retrieved with [`Neo4jImage::bolt_tls_ca`].
+    #[must_use]
+    pub fn with_bolt_tls(mut self) -> Self {
+        self.bolt_tls = Some(GeneratedCert::new("Neo4j root CA", &[]));
+        self
+    }
+
+    /// Run the container as a primary ("core") server of a cluster.
+    ///
+    /// `advertised_address` is the hostname under which this server is reachable by the other
+    /// members, and `members` are the hostnames of all primaries of the cluster, including this one.
+    /// The number of `members` is used as the initial primaries count of the cluster.
+    ///
+    /// All members have to be attached to the same network (see [`ImageExt::with_network`]),
+    /// be reachable under the given hostnames (e.g. by setting the container name
+    /// with [`ImageExt::with_container_name`]), and be started concurrently,
+    /// since each server only becomes ready once the cluster has formed.
+    /// The addresses advertised for routing are only resolvable inside of that network,
+    /// so clients on the host should use `bolt://` instead of `neo4j://`.
+    ///
+    /// Clustering is an enterprise feature: the `-enterprise` variant of the configured version
+    /// is used and the [Neo4j license agreement](https://neo4j.com/licensing/) is accepted
+    /// on your behalf by setting `NEO4J_ACCEPT_LICENSE_AGREEMENT=yes`.
+    /// Requires Neo4j `5.23` or newer.
+    ///
+    /// [`ImageExt::with_network`]: testcontainers::ImageExt::with_network
+    /// [`ImageExt::with_container_name`]: testcontainers::ImageExt::with_container_name
+    #[must_use]
+    pub fn with_cluster_mode<S: Into<String>>(
+        mut self,
+        advertised_address: impl Into<String>,
+        members: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.cluster = Some(Neo4jCluster {
+            advertised_address: advertised_address.into(),
+            members: members.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Bind-mount `host_dir` as the import directory of Neo4j,
+    /// so the files in it can be loaded with `LOAD CSV FROM 'file:///<file name>'`.
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [`Neo4j::try_with_import_dir`].
+    #[must_use]
+    pub fn with_import_dir(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_import_dir(host_dir)
+            .expect("import directory is not valid UTF-8")
+    }
+
+    /// Same as [`Neo4j::with_import_dir`], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_import_dir(
+        mut self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        self.import_dir = Some(host_dir.to_owned());
+        Ok(self)
+    }
+
+    /// Bind-mount `host_dir` as the log directory of Neo4j,
+    /// so the log files can be inspected from the host.
+    ///
+    /// The directory has to be writable by the `neo4j` user of the container.
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [`Neo4j::try_with_logs_dir`].
+    #[must_use]
+    pub fn with_logs_dir(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_logs_dir(host_dir)
+            .expect("logs directory is not valid UTF-8")
+    }
+
+    /// Same as [`Neo4j::with_logs_dir`], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_logs_dir(
+        mut self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        self.logs_dir = Some(host_dir.to_owned());
+        Ok(self)
+    }
+
+    /// Bind-mount `host_dir` as `/licenses`, the directory the license files of enterprise
+    /// plugins are expected in, e.g. with the `gds.enterprise.license_file` setting of the
+    /// Graph Data Science plugin set to `/licenses/gds.license` with [`Neo4j::with_config`].
+    ///
+    /// # Panics
+    ///
+    /// If `host_dir` isn't valid UTF-8, see [`Neo4j::try_with_licenses_dir`].
+    #[must_use]
+    pub fn with_licenses_dir(self, host_dir: impl AsRef<Path>) -> Self {
+        self.try_with_licenses_dir(host_dir)
+            .expect("licenses directory is not valid UTF-8")
+    }
+
+    /// Same as [`Neo4j::with_licenses_dir`], but returns an error instead of panicking
+    /// when `host_dir` isn't valid UTF-8.
+    pub fn try_with_licenses_dir(
+        mut self,
+        host_dir: impl AsRef<Path>,
+    ) -> Result<Self, ModuleConfigError> {
+        let host_dir = ModuleConfigError::utf8_path(host_dir.as_ref())?;
+        self.licenses_dir = Some(host_dir.to_owned());
+        Ok(self)
+    }
+
+    /// Log all executed queries to `query.log`, e.g. to assert which queries a driver sends.
+    ///
+    /// Together with [`Neo4j::with_logs_dir`], the path of the log on the host
+    /// is returned by [`Neo4jImage::query_log_path`].
+    #[must_use]
+    pub fn with_query_logging(mut self) -> Self {
+        self.query_logging = true;
+        self
+    }
+
+    /// Set an arbitrary [configuration setting](https://neo4j.com/docs/operations-manual/current/configuration/configuration-settings/)
+    /// of Neo4j, e.g. `with_config("server.memory.pagecache.size", "512M")`.
+    ///
+    /// The setting is passed as `NEO4J_*` environment variable: underscores in the key are doubled,
+    /// then periods are replaced by underscores, e.g. `NEO4J_server_memory_pagecache_size`.
+    /// Settings take precedence over the ones derived from other builder methods,
+    /// and setting the same key again overrides the previous value.
+    #[must_use]
+    pub fn with_config(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config
+            .insert(config_env_var(&key.into()), value.into());
+        self
+    }
+}
+
+/// Translate a Neo4j configuration key into the environment variable understood by the image,
+/// e.g. `dbms.memory.pagecache.size` into `NEO4J_dbms_memory_pagecache_size`.
+///
+/// Underscores are escaped by doubling them (`_` → `__`), then periods are replaced by underscores (`.` → `_`).
+fn config_env_var(key: &str) -> String {
+    format!("NEO4J_{}", key.replace('_', "__").replace('.', "_"))
 }
 
 type Value = Cow<'static, str>;
@@ -203,6 +406,10 @@ pub struct Neo4jImage {
     version: String,
     auth: Option<(String, String)>,
     env_vars: HashMap<String, String>,
+    bolt_tls_ca: Option<String>,
+    copy_to_sources: Vec<CopyToContainer>,
+    mounts: Vec<Mount>,
+    query_log_path: Option<PathBuf>,
     state: RwLock<Option<ContainerState>>,
 }
 
@@ -236,7 +443,24 @@ impl Neo4jImage {
         self.auth().map(|(_, pass)| pass)
     }
 
+    /// Return the PEM encoded CA certificate which issued the Bolt server certificate.
+    /// If Bolt TLS is not enabled with [`Neo4j::with_bolt_tls`], `None` is returned.
+    #[must_use]
+    pub fn bolt_tls_ca(&self) -> Option<&str> {
+        self.bolt_tls_ca.as_deref()
+    }
+
+    /// Return the host path of the query log.
+    /// If query logging is not enabled with [`Neo4j::with_query_logging`]
+    /// or no [`Neo4j::with_logs_dir`] is mounted, `None` is returned.
+    #[must_use]
+    pub fn query_log_path(&self) -> Option<&Path> {
+        self.query_log_path.as_deref()
+    }
+
     /// Return the port to connect to the Neo4j server via Bolt over IPv4.
+    ///
+    /// Convenience wrapper for the host port mapped to [`BOLT_PORT`] of the started container.
     pub fn bolt_port_ipv4(&self) -> Result<u16, TestcontainersError> {
         self.state
             .read()
@@ -245,10 +469,12 @@ impl Neo4jImage {
             .ok_or_else(|| {
                 TestcontainersError::other("Container must be started before port can be retrieved")
             })?
-            .host_port_ipv4(7687.tcp())
+            .host_port_ipv4(BOLT_PORT)
     }
 
     /// Return the port to connect to the Neo4j server via Bolt over IPv6.
+    ///
+    /// Convenience wrapper for the host port mapped to [`BOLT_PORT`] of the started container.
     pub fn bolt_port_ipv6(&self) -> Result<u16, TestcontainersError> {
         self.state
             .read()
@@ -257,10 +483,12 @@ impl Neo4jImage {
             .ok_or_else(|| {
                 TestcontainersError::other("Container must be started before port can be retrieved")
             })?
-            .host_port_ipv6(7687.tcp())
+            .host_port_ipv6(BOLT_PORT)
     }
 
     /// Return the port to connect to the Neo4j server via HTTP over IPv4.
+    ///
+    /// Convenience wrapper for the host port mapped to [`HTTP_PORT`] of the started container.
     pub fn http_port_ipv4(&self) -> Result<u16, TestcontainersError> {
         self.state
             .read()
@@ -269,10 +497,12 @@ impl Neo4jImage {
             .ok_or_else(|| {
                 TestcontainersError::other("Container must be started before port can be retrieved")
             })?
-            .host_port_ipv4(7474.tcp())
+            .host_port_ipv4(HTTP_PORT)
     }
 
     /// Return the port to connect to the Neo4j server via HTTP over IPv6.
+    ///
+    /// Convenience wrapper for the host port mapped to [`HTTP_PORT`] of the started container.
     pub fn http_port_ipv6(&self) -> Result<u16, TestcontainersError> {
         self.state
             .read()
@@ -281,7 +511,7 @@ impl Neo4jImage {
             .ok_or_else(|| {
                 TestcontainersError::other("Container must be started before port can be retrieved")
             })?
-            .host_port_ipv6(7474.tcp())
+            .host_port_ipv6(HTTP_PORT)
     }
 }
 
@@ -307,6 +537,14 @@ impl Image for Neo4jImage {
         &self.env_vars
     }
 
+    fn copy_to_sources(&self) -> impl IntoIterator<Item = &CopyToContainer> {
+        &self.copy_to_sources
+    }
+
+    fn mounts(&self) -> impl IntoIterator<Item = &Mount> {
+        &self.mounts
+    }
+
     fn exec_after_start(
         &self,
         cs: ContainerState,
@@ -359,6 +597,71 @@ impl Neo4j {
         }
     }
 
+    fn tls_env(&self) -> impl IntoIterator<Item = (String, String)> {
+        if self.bolt_tls.is_none() {
+            return Vec::new();
+        }
+
+        [
+            ("NEO4J_server_bolt_tls__level", "REQUIRED"),
+            ("NEO4J_dbms_ssl_policy_bolt_enabled", "true"),
+            (
+                "NEO4J_dbms_ssl_policy_bolt_base__directory",
+                BOLT_TLS_DIRECTORY,
+            ),
+            ("NEO4J_dbms_ssl_policy_bolt_private__key", "private.key"),
+            (
+                "NEO4J_dbms_ssl_policy_bolt_public__certificate",
+                "public.crt",
+            ),
+            ("NEO4J_dbms_ssl_policy_bolt_client__auth", "NONE"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+    }
+
+    fn cluster_env(&self) -> impl IntoIterator<Item = (String, String)> {
+        let Some(cluster) = &self.cluster else {
+            return Vec::new();
+        };
+
+        let endpoints = cluster
+            .members
+            .iter()
+            .map(|member| format!("{member}:6000"))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        vec![
+            (
+                "NEO4J_ACCEPT_LICENSE_AGREEMENT".to_owned(),
+                "yes".to_owned(),
+            ),
+            (
+                "NEO4J_initial_server_mode__constraint".to_owned(),
+                "PRIMARY".to_owned(),
+            ),
+            (
+                "NEO4J_server_cluster_system__database__mode".to_owned(),
+                "PRIMARY".to_owned(),
+            ),
+            (
+                "NEO4J_initial_dbms_default__primaries__count".to_owned(),
+                cluster.members.len().to_string(),
+            ),
+            (
+                "NEO4J_dbms_cluster_discovery_version".to_owned(),
+                "V2_ONLY".to_owned(),
+            ),
+            ("NEO4J_dbms_cluster_endpoints".to_owned(), endpoints),
+            (
+                "NEO4J_server_default__advertised__address".to_owned(),
+                cluster.advertised_address.clone(),
+            ),
+        ]
+    }
+
     fn build(self) -> Neo4jImage {
         let mut env_vars = HashMap::new();
 
@@ -374,16 +677,81 @@ impl Neo4j {
             env_vars.insert(key, value);
         }
 
+        for (key, value) in self.tls_env() {
+            env_vars.insert(key, value);
+        }
+
+        for (key, value) in self.cluster_env() {
+            env_vars.insert(key, value);
+        }
+
+        if self.import_dir.is_some() {
+            env_vars.insert(
+                "NEO4J_server_directories_import".to_owned(),
+                IMPORT_DIRECTORY.to_owned(),
+            );
+        }
+        if self.query_logging {
+            env_vars.insert("NEO4J_db_logs_query_enabled".to_owned(), "INFO".to_owned());
+        }
+
+        env_vars.extend(self.config);
+        let mounts = self
+            .import_dir
+            .iter()
+            .map(|host_dir| Mount::bind_mount(host_dir, IMPORT_DIRECTORY))
+            .chain(
+                self.logs_dir
+                    .iter()
+                    .map(|host_dir| Mount::bind_mount(host_dir, LOGS_DIRECTORY)),
+            )
+            .chain(
+                self.licenses_dir
+                    .iter()
+                    .map(|host_dir| Mount::bind_mount(host_dir, LICENSES_DIRECTORY)),
+            )
+            .collect();
+        let query_log_path = self
+            .logs_dir
+            .as_ref()
+            .filter(|_| self.query_logging)
+            .map(|host_dir| Path::new(host_dir).join("query.log"));
+
+        let copy_to_sources = self
+            .bolt_tls
+            .iter()
+            .flat_map(|tls| {
+                [
+                    CopyToContainer::new(
+                        CopyDataSource::Data(tls.cert.clone().into_bytes()),
+                        format!("{BOLT_TLS_DIRECTORY}/public.crt"),
+                    ),
+                    CopyToContainer::new(
+                        CopyDataSource::Data(tls.key.clone().into_bytes()),
+                        format!("{BOLT_TLS_DIRECTORY}/private.key"),
+                    ),
+                ]
+            })
+            .collect();
+        let bolt_tls_ca = self.bolt_tls.map(|tls| tls.ca);
+
         let auth = self
             .user
             .and_then(|user| self.pass.map(|pass| (user.into_owned(), pass.into_owned())));
 
-        let version = self.version.into_owned();
+        let mut version = self.version.into_owned();
+        if self.cluster.is_some() && !version.ends_with("enterprise") {
+            version.push_str("-enterprise");
+        }
 
         Neo4jImage {
             version,
             auth,
             env_vars,
+            bolt_tls_ca,
+            copy_to_sources,
+            mounts,
+            query_log_path,
             state: RwLock::new(None),
         }
     }
@@ -407,13 +775,15 @@ impl std::fmt::Debug for Neo4jImage {
             .field("version", &self.version)
             .field("auth", &self.auth())
             .field("env_vars", &self.env_vars)
+            .field("bolt_tls_ca", &self.bolt_tls_ca)
             .finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use neo4rs::Graph;
+    use neo4rs::{ConfigBuilder, Graph};
+    use testcontainers::{core::ExecCommand, ContainerAsync, ImageExt};
 
     use super::*;
     use crate::testcontainers::runners::AsyncRunner;
@@ -433,6 +803,56 @@ mod tests {
         assert_eq!(neo4j.version, "4");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn import_dir_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/neo4j-import-\xff"));
+        let result = Neo4j::new().try_with_import_dir(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn logs_dir_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/neo4j-logs-\xff"));
+        let result = Neo4j::new().try_with_logs_dir(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn licenses_dir_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/neo4j-licenses-\xff"));
+        let result = Neo4j::new().try_with_licenses_dir(path);
+        assert_eq!(
+            result.unwrap_err(),
+            ModuleConfigError::NonUtf8Path(path.to_path_buf())
+        );
+    }
+
+    #[test]
+    fn licenses_dir_is_mounted() {
+        let neo4j = Neo4j::new()
+            .with_licenses_dir("/tmp/neo4j-licenses")
+            .build();
+        let mounts = neo4j.mounts().into_iter().collect::<Vec<_>>();
+        assert_eq!(mounts.len(), 1);
+        assert_eq!(mounts[0].source(), Some("/tmp/neo4j-licenses"));
+        assert_eq!(mounts[0].target(), Some("/licenses"));
+    }
+
     #[test]
     fn set_user() {
         let neo4j = Neo4j::new().with_user("Benutzer").build();
@@ -466,6 +886,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn config_env_var_escaping() {
+        assert_eq!(
+            config_env_var("dbms.memory.pagecache.size"),
+            "NEO4J_dbms_memory_pagecache_size"
+        );
+        assert_eq!(
+            config_env_var("dbms.security.auth_minimum_password_length"),
+            "NEO4J_dbms_security_auth__minimum__password__length"
+        );
+        assert_eq!(
+            config_env_var("db.tx_log.rotation.retention_policy"),
+            "NEO4J_db_tx__log_rotation_retention__policy"
+        );
+    }
+
+    #[test]
+    fn with_config_overrides() {
+        let neo4j = Neo4j::new()
+            .with_password("1337")
+            .with_config("server.memory.heap.max_size", "512M")
+            .with_config("server.memory.heap.max_size", "1G")
+            .with_config("dbms.security.auth_minimum_password_length", "2")
+            .build();
+        assert_eq!(
+            neo4j
+                .env_vars
+                .get("NEO4J_server_memory_heap_max__size")
+                .unwrap(),
+            "1G"
+        );
+        assert_eq!(
+            neo4j
+                .env_vars
+                .get("NEO4J_dbms_security_auth__minimum__password__length")
+                .unwrap(),
+            "2"
+        );
+    }
+
     #[test]
     fn disable_auth() {
         let neo4j = Neo4j::new().without_authentication().build();
@@ -520,6 +980,11 @@ mod tests {
             container.image().bolt_port_ipv4()?
         );
 
+        assert_eq!(
+            container.get_host_port_ipv4(BOLT_PORT).await?,
+            container.image().bolt_port_ipv4()?
+        );
+
         let auth_user = container.image().user().expect("default user");
         let auth_pass = container.image().password().expect("default password");
 
@@ -530,4 +995,237 @@ mod tests {
         assert_eq!(1, value);
         Ok(())
     }
+
+    #[test]
+    fn bolt_tls_definition() {
+        let neo4j = Neo4j::new().with_bolt_tls().build();
+        assert!(neo4j.bolt_tls_ca().is_some());
+        assert_eq!(
+            neo4j.env_vars.get("NEO4J_server_bolt_tls__level").unwrap(),
+            "REQUIRED"
+        );
+        assert_eq!(neo4j.copy_to_sources.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_works_with_bolt_tls() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let container = Neo4j::default().with_bolt_tls().start().await?;
+
+        let ca_path = std::env::temp_dir().join(format!("neo4j-ca-{}.pem", container.id()));
+        std::fs::write(
+            &ca_path,
+            container.image().bolt_tls_ca().expect("bolt tls ca"),
+        )?;
+
+        let config = ConfigBuilder::default()
+            .uri(format!(
+                "neo4j+s://localhost:{}",
+                container.image().bolt_port_ipv4()?
+            ))
+            .user(container.image().user().expect("default user"))
+            .password(container.image().password().expect("default password"))
+            .with_client_certificate(&ca_path)
+            .build()?;
+
+        let graph = Graph::connect(config).await?;
+        let mut result = graph.execute(neo4rs::query("RETURN 1")).await?;
+        let row = result.next().await?.unwrap();
+        let value: i64 = row.get("1")?;
+        assert_eq!(1, value);
+
+        std::fs::remove_file(ca_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn cluster_mode_definition() {
+        let neo4j = Neo4j::new()
+            .with_cluster_mode("core1", ["core1", "core2", "core3"])
+            .build();
+        assert_eq!(neo4j.version(), "5-enterprise");
+        assert_eq!(
+            neo4j.env_vars.get("NEO4J_dbms_cluster_endpoints").unwrap(),
+            "core1:6000,core2:6000,core3:6000"
+        );
+        assert_eq!(
+            neo4j
+                .env_vars
+                .get("NEO4J_initial_dbms_default__primaries__count")
+                .unwrap(),
+            "3"
+        );
+        assert_eq!(
+            neo4j
+                .env_vars
+                .get("NEO4J_server_default__advertised__address")
+                .unwrap(),
+            "core1"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_loads_csv_from_import_dir() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let import_dir = std::env::temp_dir().join(format!("neo4j-import-{}", std::process::id()));
+        std::fs::create_dir_all(&import_dir)?;
+        std::fs::write(import_dir.join("data.csv"), "name\nAda\nGrace\n")?;
+
+        let container = Neo4j::default()
+            .with_import_dir(&import_dir)
+            .start()
+            .await?;
+
+        let uri = format!(
+            "bolt://{}:{}",
+            container.get_host().await?,
+            container.image().bolt_port_ipv4()?
+        );
+        let auth_user = container.image().user().expect("default user");
+        let auth_pass = container.image().password().expect("default password");
+        let graph = Graph::new(uri, auth_user, auth_pass).await?;
+
+        graph
+            .run(neo4rs::query(
+                "LOAD CSV WITH HEADERS FROM 'file:///data.csv' AS row CREATE (:Person {name: row.name})",
+            ))
+            .await?;
+        let mut result = graph
+            .execute(neo4rs::query("MATCH (p:Person) RETURN count(p) AS count"))
+            .await?;
+        let row = result.next().await?.unwrap();
+        let count: i64 = row.get("count")?;
+        assert_eq!(2, count);
+
+        std::fs::remove_dir_all(import_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn query_logging_definition() {
+        let neo4j = Neo4j::new().with_query_logging().build();
+        assert_eq!(
+            neo4j.env_vars.get("NEO4J_db_logs_query_enabled").unwrap(),
+            "INFO"
+        );
+        assert!(neo4j.query_log_path().is_none());
+
+        let neo4j = Neo4j::new()
+            .with_query_logging()
+            .with_logs_dir("/tmp/neo4j-logs")
+            .build();
+        assert_eq!(
+            neo4j.query_log_path(),
+            Some(Path::new("/tmp/neo4j-logs/query.log"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_logs_queries() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let logs_dir = std::env::temp_dir().join(format!("neo4j-logs-{}", std::process::id()));
+        std::fs::create_dir_all(&logs_dir)?;
+        // the container runs as the `neo4j` user, which has to be able to write the logs
+        std::fs::set_permissions(&logs_dir, std::fs::Permissions::from_mode(0o777))?;
+
+        let container = Neo4j::default()
+            .with_query_logging()
+            .with_logs_dir(&logs_dir)
+            .start()
+            .await?;
+
+        let uri = format!(
+            "bolt://{}:{}",
+            container.get_host().await?,
+            container.image().bolt_port_ipv4()?
+        );
+        let auth_user = container.image().user().expect("default user");
+        let auth_pass = container.image().password().expect("default password");
+        let graph = Graph::new(uri, auth_user, auth_pass).await?;
+        graph
+            .run(neo4rs::query("RETURN 'query-logging-marker' AS marker"))
+            .await?;
+
+        let query_log_path = container.image().query_log_path().expect("query log path");
+        // the query log is written asynchronously
+        let mut logged = false;
+        for _ in 0..50 {
+            let query_log = std::fs::read_to_string(query_log_path).unwrap_or_default();
+            if query_log.contains("query-logging-marker") {
+                logged = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(logged, "query not found in {}", query_log_path.display());
+
+        drop(container);
+        let _ = std::fs::remove_dir_all(logs_dir);
+        Ok(())
+    }
+
+    async fn cypher_shell(
+        container: &ContainerAsync<Neo4jImage>,
+        address: &str,
+        query: &str,
+    ) -> Result<String, Box<dyn std::error::Error + 'static>> {
+        let mut result = container
+            .exec(ExecCommand::new([
+                "cypher-shell",
+                "-a",
+                address,
+                "-u",
+                container.image().user().expect("default user"),
+                "-p",
+                container.image().password().expect("default password"),
+                "--format",
+                "plain",
+                query,
+            ]))
+            .await?;
+        Ok(String::from_utf8(result.stdout_to_vec().await?)?)
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Neo4j enterprise edition and starts three containers"]
+    async fn it_works_as_cluster() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let suffix = std::process::id();
+        let network = format!("neo4j-cluster-{suffix}");
+        let members: Vec<String> = (1..=3).map(|i| format!("neo4j-core{i}-{suffix}")).collect();
+
+        let containers = futures::future::try_join_all(members.iter().map(|member| {
+            Neo4j::default()
+                .with_cluster_mode(member, &members)
+                .with_network(&network)
+                .with_container_name(member)
+                .with_startup_timeout(std::time::Duration::from_secs(300))
+                .start()
+        }))
+        .await?;
+
+        // writes sent with the routing `neo4j://` scheme are routed to the leader of `neo4j`
+        cypher_shell(
+            &containers[0],
+            "neo4j://localhost:7687",
+            "CREATE (:Cluster {name: 'testcontainers'})",
+        )
+        .await?;
+
+        let writers = cypher_shell(
+            &containers[1],
+            "neo4j://localhost:7687",
+            "SHOW DATABASE neo4j YIELD address, writer WHERE writer RETURN address",
+        )
+        .await?;
+        assert_eq!(writers.lines().skip(1).count(), 1);
+
+        let count = cypher_shell(
+            &containers[2],
+            "neo4j://localhost:7687",
+            "MATCH (n:Cluster) RETURN count(n)",
+        )
+        .await?;
+        assert_eq!(count.lines().nth(1), Some("1"));
+        Ok(())
+    }
 }
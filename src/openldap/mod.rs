@@ -1,15 +1,26 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, time::Duration};
 
 use parse_display::{Display, FromStr};
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
-    core::{ContainerPort, WaitFor},
-    CopyDataSource, CopyToContainer, Image,
+    core::{CmdWaitFor, ContainerPort, ContainerState, ExecCommand, WaitFor},
+    ContainerAsync, CopyDataSource, CopyToContainer, Image, TestcontainersError,
 };
 
+use crate::util::retry_until_success;
+
 const NAME: &str = "bitnami/openldap";
 const TAG: &str = "2.6.8";
-const OPENLDAP_PORT: ContainerPort = ContainerPort::Tcp(1389);
-const OPENLDAPS_PORT: ContainerPort = ContainerPort::Tcp(1636);
+/// Port of the plain LDAP listener of the [`OpenLDAP`] container.
+/// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
+pub const OPENLDAP_PORT: ContainerPort = ContainerPort::Tcp(1389);
+/// Port of the LDAPS listener of the [`OpenLDAP`] container, which only accepts connections
+/// if TLS has been configured with [`OpenLDAP::with_tls`].
+/// Can be rebound externally via [`testcontainers::core::ImageExt::with_mapped_port`]
+pub const OPENLDAPS_PORT: ContainerPort = ContainerPort::Tcp(1636);
+const PPOLICY_LDIF_PATH: &str = "/ppolicy/default_policy.ldif";
+const PPOLICY_CONFIG_LDIF_PATH: &str = "/ppolicy/default_policy_config.ldif";
 
 /// Module to work with [`OpenLDAP`] inside of tests.
 ///
@@ -25,7 +36,9 @@ const OPENLDAPS_PORT: ContainerPort = ContainerPort::Tcp(1636);
 /// let connection_string = format!(
 ///     "ldap://{}:{}",
 ///     openldap_instance.get_host().unwrap(),
-///     openldap_instance.get_host_port_ipv4(1389).unwrap(),
+///     openldap_instance
+///         .get_host_port_ipv4(openldap::OPENLDAP_PORT)
+///         .unwrap(),
 /// );
 /// let mut conn = ldap3::LdapConn::new(&connection_string).unwrap();
 /// let ldap3::SearchResult(rs, _) = conn
@@ -48,6 +61,8 @@ pub struct OpenLDAP {
     env_vars: HashMap<String, String>,
     users: Vec<User>,
     copy_to_sources: Vec<CopyToContainer>,
+    ppolicy: bool,
+    ppolicy_default_dn: Option<String>,
 }
 #[derive(Debug, Clone)]
 struct User {
@@ -280,6 +295,91 @@ impl OpenLDAP {
             .insert("LDAP_TLS_CA_FILE".to_owned(), "/certs/ca.crt".to_owned());
         self
     }
+
+    /// Enables the password policy (`ppolicy`) overlay with lockout and loads the default policy.
+    ///
+    /// `default_policy_ldif` is added by the admin after startup, so it may also contain
+    /// the parent entries of the policy (e.g. `ou=policies`).
+    /// The entry with the `pwdPolicy` objectClass becomes the default policy of all accounts.
+    /// Default: `None`
+    pub fn with_ppolicy(mut self, default_policy_ldif: impl ToString) -> Self {
+        let default_policy_ldif = default_policy_ldif.to_string();
+        self.env_vars
+            .insert("LDAP_CONFIGURE_PPOLICY".to_owned(), "yes".to_owned());
+        self.env_vars
+            .insert("LDAP_PPOLICY_USE_LOCKOUT".to_owned(), "yes".to_owned());
+
+        self.ppolicy_default_dn = ppolicy_dn(&default_policy_ldif);
+        if let Some(policy_dn) = &self.ppolicy_default_dn {
+            let config_ldif = format!(
+                "dn: olcOverlay={{0}}ppolicy,olcDatabase={{2}}mdb,cn=config\n\
+                 changetype: modify\n\
+                 replace: olcPPolicyDefault\n\
+                 olcPPolicyDefault: {policy_dn}\n"
+            );
+            self.copy_to_sources.push(CopyToContainer::new(
+                config_ldif.into_bytes(),
+                PPOLICY_CONFIG_LDIF_PATH,
+            ));
+        }
+        self.copy_to_sources.push(CopyToContainer::new(
+            default_policy_ldif.into_bytes(),
+            PPOLICY_LDIF_PATH,
+        ));
+        self.ppolicy = true;
+        self
+    }
+
+    /// Base DN of the LDAP tree.
+    fn ldap_root(&self) -> &str {
+        self.env_vars
+            .get("LDAP_ROOT")
+            .map_or("dc=example,dc=org", String::as_str)
+    }
+
+    /// Whether TLS has been configured with [`OpenLDAP::with_tls`].
+    fn tls_enabled(&self) -> bool {
+        self.env_vars
+            .get("LDAP_ENABLE_TLS")
+            .is_some_and(|enabled| enabled == "yes")
+    }
+
+    /// Distinguished name of the admin account.
+    fn admin_dn(&self) -> String {
+        format!(
+            "cn={},{}",
+            self.env_vars
+                .get("LDAP_ADMIN_USERNAME")
+                .map_or("admin", String::as_str),
+            self.ldap_root(),
+        )
+    }
+
+    /// Password of the admin account.
+    fn admin_password(&self) -> &str {
+        self.env_vars
+            .get("LDAP_ADMIN_PASSWORD")
+            .map_or("adminpassword", String::as_str)
+    }
+}
+
+/// Finds the dn of the `pwdPolicy` entry in the given LDIF.
+fn ppolicy_dn(ldif: &str) -> Option<String> {
+    ldif.split("\n\n").find_map(|entry| {
+        let is_policy = entry.lines().any(|line| {
+            line.split_once(':').is_some_and(|(key, value)| {
+                key.trim().eq_ignore_ascii_case("objectClass")
+                    && value.trim().eq_ignore_ascii_case("pwdPolicy")
+            })
+        });
+        if !is_policy {
+            return None;
+        }
+        entry
+            .lines()
+            .find_map(|line| line.strip_prefix("dn:"))
+            .map(|dn| dn.trim().to_owned())
+    })
 }
 
 /// hash to be used in generation of user passwords.
@@ -400,10 +500,54 @@ impl Default for OpenLDAP {
             users: vec![],
             env_vars: HashMap::new(),
             copy_to_sources: vec![],
+            ppolicy: false,
+            ppolicy_default_dn: None,
         }
     }
 }
 
+/// Returns the `ldap://host:port` URL of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`OPENLDAP_PORT`].
+pub async fn ldap_url(container: &ContainerAsync<OpenLDAP>) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(OPENLDAP_PORT).await?;
+    Ok(format!("ldap://{host}:{port}"))
+}
+
+/// Returns the `ldaps://host:port` URL of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`OPENLDAPS_PORT`].
+///
+/// The URL is only meaningful if TLS has been configured with [`OpenLDAP::with_tls`],
+/// otherwise nothing accepts connections on it.
+pub async fn ldaps_url(
+    container: &ContainerAsync<OpenLDAP>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(OPENLDAPS_PORT).await?;
+    Ok(format!("ldaps://{host}:{port}"))
+}
+
+/// Returns the `ldap://host:port` URL of a container started with the `SyncRunner`,
+/// using the host port mapped to [`OPENLDAP_PORT`].
+#[cfg(feature = "blocking")]
+pub fn ldap_url_blocking(container: &Container<OpenLDAP>) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(OPENLDAP_PORT)?;
+    Ok(format!("ldap://{host}:{port}"))
+}
+
+/// Returns the `ldaps://host:port` URL of a container started with the `SyncRunner`,
+/// using the host port mapped to [`OPENLDAPS_PORT`].
+///
+/// The URL is only meaningful if TLS has been configured with [`OpenLDAP::with_tls`],
+/// otherwise nothing accepts connections on it.
+#[cfg(feature = "blocking")]
+pub fn ldaps_url_blocking(container: &Container<OpenLDAP>) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(OPENLDAPS_PORT)?;
+    Ok(format!("ldaps://{host}:{port}"))
+}
+
 impl Image for OpenLDAP {
     fn name(&self) -> &str {
         NAME
@@ -416,10 +560,8 @@ impl Image for OpenLDAP {
     fn ready_conditions(&self) -> Vec<WaitFor> {
         // maybe OpenLDAP will have a healthcheck someday
         // https://github.com/osixia/docker-openldap/issues/637
-        vec![
-            WaitFor::message_on_stderr("** Starting slapd **"),
-            WaitFor::seconds(2), // ideally, one should wait for a port instead of waiting a fixed time
-        ]
+        // slapd accepting binds is verified by an exec, see `exec_after_start`
+        vec![WaitFor::message_on_stderr("** Starting slapd **")]
     }
 
     fn env_vars(
@@ -452,6 +594,69 @@ impl Image for OpenLDAP {
             .iter()
             .collect::<Vec<&CopyToContainer>>()
     }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        let ldap_url = format!("ldap://localhost:{}", OPENLDAP_PORT.as_u16());
+        // with TLS, the LDAPS listener may start after the plain one, so it's checked instead
+        let ready_url = if self.tls_enabled() {
+            format!("ldaps://localhost:{}", OPENLDAPS_PORT.as_u16())
+        } else {
+            ldap_url.clone()
+        };
+        // the container is ready, once the admin is able to bind and search the tree,
+        // the certificate doesn't need to be valid for `localhost`, giving up after 600 attempts
+        let mut commands = vec![retry_until_success(
+            r#"LDAPTLS_REQCERT=never ldapsearch -x -H "$1" -D "$2" -w "$3" -b "$4" -s base >/dev/null 2>&1"#,
+            [
+                ready_url,
+                self.admin_dn(),
+                self.admin_password().to_owned(),
+                self.ldap_root().to_owned(),
+            ],
+            600,
+            Duration::from_millis(100),
+        )];
+
+        if !self.ppolicy {
+            return Ok(commands);
+        }
+
+        commands.push(
+            ExecCommand::new([
+                "ldapadd".to_owned(),
+                "-x".to_owned(),
+                "-H".to_owned(),
+                ldap_url,
+                "-D".to_owned(),
+                self.admin_dn(),
+                "-w".to_owned(),
+                self.admin_password().to_owned(),
+                "-f".to_owned(),
+                PPOLICY_LDIF_PATH.to_owned(),
+            ])
+            .with_cmd_ready_condition(CmdWaitFor::exit_code(0)),
+        );
+
+        if self.ppolicy_default_dn.is_some() {
+            commands.push(
+                ExecCommand::new([
+                    "ldapmodify",
+                    "-Q",
+                    "-Y",
+                    "EXTERNAL",
+                    "-H",
+                    "ldapi:///",
+                    "-f",
+                    PPOLICY_CONFIG_LDIF_PATH,
+                ])
+                .with_cmd_ready_condition(CmdWaitFor::exit_code(0)),
+            );
+        }
+        Ok(commands)
+    }
 }
 
 #[cfg(test)]
@@ -497,11 +702,7 @@ mod tests {
             .with_user("lea", "pwd3");
         let node = openldap_image.start().await?;
 
-        let connection_string = format!(
-            "ldap://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(OPENLDAP_PORT).await?,
-        );
+        let connection_string = ldap_url(&node).await?;
         let (conn, mut ldap) = LdapConnAsync::new(&connection_string).await?;
         ldap3::drive!(conn);
         let users = read_users(&mut ldap, "(cn=ma*)", &["cn"]).await?;
@@ -536,11 +737,7 @@ mod tests {
             .with_user("maximiliane", "pwd1");
         let node = openldap_image.start().await?;
 
-        let connection_string = format!(
-            "ldap://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(OPENLDAP_PORT).await?,
-        );
+        let connection_string = ldap_url(&node).await?;
         let (conn, mut ldap) = LdapConnAsync::new(&connection_string).await?;
         ldap3::drive!(conn);
         ldap.simple_bind("cn=maximiliane,ou=users,dc=example,dc=org", "pwd1")
@@ -562,11 +759,7 @@ mod tests {
             );
         let node = openldap_image.start().await?;
 
-        let connection_string = format!(
-            "ldap://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(OPENLDAP_PORT).await?,
-        );
+        let connection_string = ldap_url(&node).await?;
         let (conn, mut ldap) = LdapConnAsync::new(&connection_string).await?;
         ldap3::drive!(conn);
 
@@ -620,11 +813,7 @@ userpassword: {SSHA}1vtVpqX6Y77jAVs/1uTd/rHS8YRYEh/9
 
         let node = openldap_image.start().await?;
 
-        let connection_string = format!(
-            "ldap://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(OPENLDAP_PORT).await?,
-        );
+        let connection_string = ldap_url(&node).await?;
         let (conn, mut ldap) = LdapConnAsync::new(&connection_string).await?;
         ldap3::drive!(conn);
         ldap.simple_bind("cn=maximiliane,ou=users,dc=example,dc=org", "pwd1")
@@ -722,11 +911,8 @@ H32P9zbIKaSiPxFg5JVRW5hpQWUI1dYr3CpKP4i98w==
             .with_cert_ca(root_ca.to_string().into_bytes());
         let node = openldap_image.start().await?;
 
-        let connection_string = format!(
-            "ldaps://{}:{}",
-            node.get_host().await?,
-            node.get_host_port_ipv4(OPENLDAPS_PORT).await?,
-        );
+        // no retries: the container is only ready once the admin bind via LDAPS succeeds
+        let connection_string = ldaps_url(&node).await?;
 
         let mut builder = native_tls::TlsConnector::builder();
         let root_ca = native_tls::Certificate::from_pem(root_ca.as_bytes())?;
@@ -745,4 +931,79 @@ H32P9zbIKaSiPxFg5JVRW5hpQWUI1dYr3CpKP4i98w==
         ldap.unbind().await?;
         Ok(())
     }
+
+    #[test]
+    fn ppolicy_dn_of_policy_entry() {
+        let ldif = "dn: ou=policies,dc=example,dc=org\nobjectClass: organizationalUnit\nou: policies\n\n\
+                    dn: cn=default,ou=policies,dc=example,dc=org\nobjectClass: pwdPolicy\ncn: default\n";
+        assert_eq!(
+            ppolicy_dn(ldif).as_deref(),
+            Some("cn=default,ou=policies,dc=example,dc=org")
+        );
+    }
+
+    #[tokio::test]
+    async fn ldap_admin_bind_when_ready() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let node = OpenLDAP::default()
+            .with_base_dn("dc=testcontainers,dc=org")
+            .with_admin("root", "rootpassword")
+            .start()
+            .await?;
+
+        let connection_string = ldap_url(&node).await?;
+        // no retries: the container is only ready once the admin bind succeeds
+        let (conn, mut ldap) = LdapConnAsync::new(&connection_string).await?;
+        ldap3::drive!(conn);
+        ldap.simple_bind("cn=root,dc=testcontainers,dc=org", "rootpassword")
+            .await?
+            .success()?;
+        let (entries, _res) = ldap
+            .search(
+                "dc=testcontainers,dc=org",
+                Scope::Base,
+                "(objectClass=*)",
+                ["dn"],
+            )
+            .await?
+            .success()?;
+        assert_eq!(entries.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ldap_ppolicy_lockout() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let default_policy = r#"dn: ou=policies,dc=example,dc=org
+objectClass: organizationalUnit
+ou: policies
+
+dn: cn=default,ou=policies,dc=example,dc=org
+objectClass: top
+objectClass: device
+objectClass: pwdPolicy
+cn: default
+pwdAttribute: userPassword
+pwdLockout: TRUE
+pwdMaxFailure: 3
+pwdLockoutDuration: 300
+"#;
+        let openldap_image = OpenLDAP::default()
+            .with_user("maximiliane", "pwd1")
+            .with_ppolicy(default_policy);
+        let node = openldap_image.start().await?;
+
+        let connection_string = ldap_url(&node).await?;
+        let (conn, mut ldap) = LdapConnAsync::new(&connection_string).await?;
+        ldap3::drive!(conn);
+        let user_dn = "cn=maximiliane,ou=users,dc=example,dc=org";
+        for _ in 0..3 {
+            let res = ldap.simple_bind(user_dn, "wrong").await?;
+            assert_eq!(res.rc, 49); // invalidCredentials
+        }
+        let res = ldap.simple_bind(user_dn, "pwd1").await?;
+        assert_eq!(res.rc, 49); // locked accounts are refused like bad credentials
+        ldap.unbind().await?;
+        Ok(())
+    }
 }
@@ -1,15 +1,24 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{borrow::Cow, collections::BTreeMap, time::Duration};
 
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
     core::{CmdWaitFor, ContainerPort, ContainerState, ExecCommand, Mount, WaitFor},
-    Image, TestcontainersError,
+    ContainerAsync, Image, TestcontainersError,
 };
 
+use crate::util::retry_until_success;
+
 const NAME: &str = "apachepulsar/pulsar";
 const TAG: &str = "2.10.6";
+/// Succeeds once the transaction coordinator is ready, see [`Pulsar::with_transactions_enabled`]
+const TRANSACTION_COORDINATOR_PROBE: &str =
+    r#"bin/pulsar-admin transactions coordinator-stats 2>/dev/null | grep -q '"state" : "Ready"'"#;
 
-const PULSAR_PORT: ContainerPort = ContainerPort::Tcp(6650);
-const ADMIN_PORT: ContainerPort = ContainerPort::Tcp(8080);
+/// Port of the binary protocol of the broker inside of the container, used by [`service_url`]
+pub const PULSAR_PORT: ContainerPort = ContainerPort::Tcp(6650);
+/// Port of the HTTP service with the admin REST API inside of the container, used by [`admin_url`]
+pub const ADMIN_PORT: ContainerPort = ContainerPort::Tcp(8080);
 
 /// Module to work with [`Apache Pulsar`] inside of tests.
 /// **Requires protoc to be installed, otherwise will not build.**
@@ -18,12 +27,16 @@ const ADMIN_PORT: ContainerPort = ContainerPort::Tcp(8080);
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
 /// use testcontainers_modules::{pulsar, testcontainers::runners::SyncRunner};
 ///
 /// let pulsar = pulsar::Pulsar::default().start().unwrap();
-/// let http_port = pulsar.get_host_port_ipv4(6650).unwrap();
+/// let service_url = pulsar::service_url_blocking(&pulsar).unwrap();
+/// let admin_url = pulsar::admin_url_blocking(&pulsar).unwrap();
 ///
 /// // do something with the running pulsar instance..
+/// # }
 /// ```
 ///
 /// [`Apache Pulsar`]: https://github.com/apache/pulsar
@@ -33,6 +46,7 @@ pub struct Pulsar {
     data_mount: Mount,
     env: BTreeMap<String, String>,
     admin_commands: Vec<Vec<String>>,
+    transactions_enabled: bool,
 }
 
 impl Default for Pulsar {
@@ -44,6 +58,7 @@ impl Default for Pulsar {
             data_mount: Mount::tmpfs_mount("/pulsar/data"),
             env: BTreeMap::new(),
             admin_commands: vec![],
+            transactions_enabled: false,
         }
     }
 }
@@ -87,6 +102,88 @@ impl Pulsar {
         let topic = topic.into();
         self.with_admin_command(["topics", "create", &topic])
     }
+
+    /// Registers an additional cluster after container start, e.g. to set up geo-replication
+    ///
+    /// Tenants and namespaces which should be replicated to the cluster have to allow it,
+    /// for example with `tenants create <tenant> --allowed-clusters standalone,<name>` and
+    /// `namespaces set-clusters <namespace> --clusters standalone,<name>` admin commands,
+    /// added after this one.
+    pub fn with_cluster(self, name: impl Into<String>, broker_url: impl Into<String>) -> Self {
+        let name = name.into();
+        let broker_url = broker_url.into();
+        self.with_admin_command(["clusters", "create", &name, "--broker-url", &broker_url])
+    }
+
+    /// Enables or disables [message deduplication](https://pulsar.apache.org/docs/next/cookbooks-deduplication/)
+    /// for `namespace` after container start
+    ///
+    /// With deduplication enabled, the broker drops messages of a named producer whose
+    /// sequence id isn't higher than the last one persisted, which allows testing exactly-once producers.
+    /// The namespace has to exist, e.g. created by [`Pulsar::with_namespace`] before.
+    pub fn with_deduplication(self, namespace: impl Into<String>, enabled: bool) -> Self {
+        let namespace = namespace.into();
+        let flag = if enabled { "--enable" } else { "--disable" };
+        self.with_admin_command(["namespaces", "set-deduplication", &namespace, flag])
+    }
+
+    /// Enables [message deduplication](https://pulsar.apache.org/docs/next/cookbooks-deduplication/)
+    /// for all namespaces of the broker (`brokerDeduplicationEnabled`)
+    ///
+    /// Use [`Pulsar::with_deduplication`] to enable or disable it for a single namespace instead.
+    pub fn with_deduplication_enabled(self) -> Self {
+        self.with_config_env("brokerDeduplicationEnabled", "true")
+    }
+
+    /// Enables [transactions](https://pulsar.apache.org/docs/next/txn-use/) on the broker
+    ///
+    /// Sets `transactionCoordinatorEnabled` and `systemTopicEnabled`, as the coordinator keeps
+    /// its state in system topics. The coordinator is only started some time after the broker,
+    /// so a command run after start polls its state until it's `Ready`, failing the start if it
+    /// doesn't get ready within two minutes. Clients can open transactions as soon as the container is started.
+    pub fn with_transactions_enabled(mut self) -> Self {
+        self.transactions_enabled = true;
+        self.with_config_env("transactionCoordinatorEnabled", "true")
+            .with_config_env("systemTopicEnabled", "true")
+    }
+}
+
+/// Returns the `pulsar://` URL for clients of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`PULSAR_PORT`].
+pub async fn service_url(
+    container: &ContainerAsync<Pulsar>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(PULSAR_PORT).await?;
+    Ok(format!("pulsar://{host}:{port}"))
+}
+
+/// Returns the `pulsar://` URL for clients of a container started with the `SyncRunner`,
+/// using the host port mapped to [`PULSAR_PORT`].
+#[cfg(feature = "blocking")]
+pub fn service_url_blocking(container: &Container<Pulsar>) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(PULSAR_PORT)?;
+    Ok(format!("pulsar://{host}:{port}"))
+}
+
+/// Returns the `http://` URL of the admin REST API of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`ADMIN_PORT`].
+///
+/// The API is served below `/admin/v2`, e.g. `{admin_url}/admin/v2/tenants`.
+pub async fn admin_url(container: &ContainerAsync<Pulsar>) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(ADMIN_PORT).await?;
+    Ok(format!("http://{host}:{port}"))
+}
+
+/// Returns the `http://` URL of the admin REST API of a container started with the `SyncRunner`,
+/// using the host port mapped to [`ADMIN_PORT`].
+#[cfg(feature = "blocking")]
+pub fn admin_url_blocking(container: &Container<Pulsar>) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(ADMIN_PORT)?;
+    Ok(format!("http://{host}:{port}"))
 }
 
 impl Image for Pulsar {
@@ -127,11 +224,21 @@ impl Image for Pulsar {
         &self,
         _cs: ContainerState,
     ) -> Result<Vec<ExecCommand>, TestcontainersError> {
-        Ok(self
+        let mut commands: Vec<_> = self
             .admin_commands
             .iter()
             .map(|cmd| ExecCommand::new(cmd).with_cmd_ready_condition(CmdWaitFor::exit_code(0)))
-            .collect())
+            .collect();
+        if self.transactions_enabled {
+            // polled once a second, giving up after two minutes
+            commands.push(retry_until_success(
+                TRANSACTION_COORDINATOR_PROBE,
+                std::iter::empty::<String>(),
+                120,
+                Duration::from_secs(1),
+            ));
+        }
+        Ok(commands)
     }
 
     fn expose_ports(&self) -> &[ContainerPort] {
@@ -185,11 +292,7 @@ mod tests {
             .await
             .unwrap();
 
-        let endpoint = format!(
-            "pulsar://0.0.0.0:{}",
-            pulsar.get_host_port_ipv4(6650).await?
-        );
-        let client = pulsar::Pulsar::builder(endpoint, TokioExecutor)
+        let client = pulsar::Pulsar::builder(service_url(&pulsar).await?, TokioExecutor)
             .build()
             .await?;
 
@@ -223,11 +326,7 @@ mod tests {
             .await
             .unwrap();
 
-        let endpoint = format!(
-            "pulsar://0.0.0.0:{}",
-            pulsar.get_host_port_ipv4(6650).await?
-        );
-        let client = pulsar::Pulsar::builder(endpoint, TokioExecutor)
+        let client = pulsar::Pulsar::builder(service_url(&pulsar).await?, TokioExecutor)
             .build()
             .await?;
 
@@ -240,4 +339,191 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[ignore = "registers a cluster without a running broker behind it"]
+    async fn pulsar_geo_replication_cluster() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let pulsar = Pulsar::default()
+            .with_cluster("remote", "pulsar://remote:6650")
+            .with_admin_command([
+                "tenants",
+                "create",
+                "test",
+                "--allowed-clusters",
+                "standalone,remote",
+            ])
+            .with_namespace("test/geo")
+            .with_admin_command([
+                "namespaces",
+                "set-clusters",
+                "test/geo",
+                "--clusters",
+                "standalone,remote",
+            ])
+            .start()
+            .await?;
+
+        let mut clusters = pulsar
+            .exec(ExecCommand::new(["bin/pulsar-admin", "clusters", "list"]))
+            .await?;
+        let clusters = String::from_utf8(clusters.stdout_to_vec().await?)?;
+        assert!(clusters.lines().any(|c| c.trim_matches('"') == "remote"));
+        assert!(clusters
+            .lines()
+            .any(|c| c.trim_matches('"') == "standalone"));
+
+        let mut replication = pulsar
+            .exec(ExecCommand::new([
+                "bin/pulsar-admin",
+                "namespaces",
+                "get-clusters",
+                "test/geo",
+            ]))
+            .await?;
+        let replication = String::from_utf8(replication.stdout_to_vec().await?)?;
+        assert!(replication.lines().any(|c| c.trim_matches('"') == "remote"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pulsar_deduplication() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let topic = "persistent://test/dedup/test-topic";
+
+        let pulsar = Pulsar::default()
+            .with_tenant("test")
+            .with_namespace("test/dedup")
+            .with_deduplication("test/dedup", true)
+            .start()
+            .await?;
+
+        // the python client of the image allows to set sequence ids explicitly
+        let script = format!(
+            r#"
+import pulsar
+client = pulsar.Client("pulsar://localhost:6650")
+consumer = client.subscribe("{topic}", "dedup-test")
+producer = client.create_producer("{topic}", producer_name="dedup-producer")
+for sequence_id, payload in [(1, "first"), (1, "duplicate"), (2, "second")]:
+    producer.send(payload.encode(), sequence_id=sequence_id)
+received = []
+while True:
+    try:
+        message = consumer.receive(timeout_millis=2000)
+    except Exception:
+        break
+    received.append(message.data().decode())
+    consumer.acknowledge(message)
+print(",".join(received))
+client.close()
+"#
+        );
+        let mut result = pulsar
+            .exec(ExecCommand::new(["python3", "-c", &script]))
+            .await?;
+        let output = String::from_utf8(result.stdout_to_vec().await?)?;
+        // the client may log to stdout as well, the result is printed last
+        assert_eq!(output.trim().lines().last(), Some("first,second"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pulsar_admin_url() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let pulsar = Pulsar::default().with_tenant("test").start().await?;
+
+        let tenants = reqwest::get(format!("{}/admin/v2/tenants", admin_url(&pulsar).await?))
+            .await?
+            .json::<Vec<String>>()
+            .await?;
+        assert!(tenants.contains(&"test".to_owned()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn pulsar_transactions_and_deduplication_config() {
+        let pulsar = Pulsar::default()
+            .with_transactions_enabled()
+            .with_deduplication_enabled();
+        assert_eq!(
+            pulsar.env["PULSAR_PREFIX_transactionCoordinatorEnabled"],
+            "true"
+        );
+        assert_eq!(pulsar.env["PULSAR_PREFIX_systemTopicEnabled"], "true");
+        assert_eq!(
+            pulsar.env["PULSAR_PREFIX_brokerDeduplicationEnabled"],
+            "true"
+        );
+        // the coordinator is awaited by a command of its own, not an admin command
+        assert!(pulsar.transactions_enabled);
+        assert!(pulsar.admin_commands.is_empty());
+    }
+
+    #[tokio::test]
+    async fn pulsar_transactions() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let topic = "persistent://public/default/txn-topic";
+
+        let pulsar = Pulsar::default()
+            .with_transactions_enabled()
+            .start()
+            .await?;
+
+        let client = pulsar::Pulsar::builder(service_url(&pulsar).await?, TokioExecutor)
+            .build()
+            .await?;
+        let mut consumer: Consumer<Vec<u8>, _> = client
+            .consumer()
+            .with_topic(topic)
+            .with_subscription("txn-test")
+            .build()
+            .await?;
+
+        // the Rust client doesn't support transactions, the Java one of the image does
+        let source = format!(
+            r#"
+import java.util.concurrent.TimeUnit;
+import org.apache.pulsar.client.api.*;
+import org.apache.pulsar.client.api.transaction.Transaction;
+
+public class Txn {{
+    public static void main(String[] args) throws Exception {{
+        PulsarClient client = PulsarClient.builder()
+            .serviceUrl("pulsar://localhost:6650")
+            .enableTransaction(true)
+            .build();
+        Producer<String> producer = client.newProducer(Schema.STRING)
+            .topic("{topic}")
+            .sendTimeout(0, TimeUnit.SECONDS)
+            .create();
+        Transaction aborted = client.newTransaction().withTransactionTimeout(1, TimeUnit.MINUTES).build().get();
+        producer.newMessage(aborted).value("aborted").send();
+        aborted.abort().get();
+        Transaction committed = client.newTransaction().withTransactionTimeout(1, TimeUnit.MINUTES).build().get();
+        producer.newMessage(committed).value("committed").send();
+        committed.commit().get();
+        client.close();
+    }}
+}}
+"#
+        );
+        pulsar
+            .exec(
+                ExecCommand::new([
+                    "sh",
+                    "-c",
+                    r#"printf '%s' "$1" > /tmp/Txn.java && java -cp '/pulsar/lib/*' /tmp/Txn.java"#,
+                    "sh",
+                    &source,
+                ])
+                .with_cmd_ready_condition(CmdWaitFor::exit_code(0)),
+            )
+            .await?;
+
+        // only the message of the committed transaction is delivered
+        let message = consumer.next().await.unwrap()?;
+        assert_eq!(message.payload.data, b"committed");
+
+        Ok(())
+    }
 }
@@ -1,7 +1,18 @@
-use testcontainers::{core::WaitFor, Image};
+use testcontainers::{
+    core::{ContainerPort, WaitFor},
+    Image,
+};
 
 const NAME: &str = "rabbitmq";
 const TAG: &str = "3.8.22-management";
+/// Port of the AMQP listener of [`RabbitMQ`] inside of the container
+///
+/// [`RabbitMQ`]: https://www.rabbitmq.com/
+pub const RABBITMQ_PORT: ContainerPort = ContainerPort::Tcp(5672);
+/// Port of the [`RabbitMQ Management HTTP API`] and UI inside of the container
+///
+/// [`RabbitMQ Management HTTP API`]: https://www.rabbitmq.com/management.html#http-api
+pub const RABBITMQ_MANAGEMENT_PORT: ContainerPort = ContainerPort::Tcp(15672);
 
 /// Module to work with [`RabbitMQ`] inside of tests.
 ///
@@ -19,7 +30,9 @@ const TAG: &str = "3.8.22-management";
 /// let amqp_url = format!(
 ///     "amqp://{}:{}",
 ///     rabbitmq_instance.get_host().unwrap(),
-///     rabbitmq_instance.get_host_port_ipv4(5672).unwrap()
+///     rabbitmq_instance
+///         .get_host_port_ipv4(rabbitmq::RABBITMQ_PORT)
+///         .unwrap()
 /// );
 ///
 /// // do something with the started rabbitmq instance..
@@ -46,6 +59,10 @@ impl Image for RabbitMq {
         TAG
     }
 
+    fn expose_ports(&self) -> &[ContainerPort] {
+        &[RABBITMQ_PORT, RABBITMQ_MANAGEMENT_PORT]
+    }
+
     fn ready_conditions(&self) -> Vec<WaitFor> {
         vec![WaitFor::message_on_stdout(
             "Server startup complete; 4 plugins started.",
@@ -67,7 +84,10 @@ mod tests {
         BasicProperties, Connection, ConnectionProperties, ExchangeKind,
     };
 
-    use crate::{rabbitmq, testcontainers::runners::AsyncRunner};
+    use crate::{
+        rabbitmq,
+        testcontainers::{runners::AsyncRunner, Image},
+    };
 
     #[tokio::test]
     async fn rabbitmq_produce_and_consume_messages(
@@ -78,7 +98,9 @@ mod tests {
         let amqp_url = format!(
             "amqp://{}:{}",
             rabbit_node.get_host().await?,
-            rabbit_node.get_host_port_ipv4(5672).await?
+            rabbit_node
+                .get_host_port_ipv4(rabbitmq::RABBITMQ_PORT)
+                .await?
         );
 
         let options = ConnectionProperties::default();
@@ -155,4 +177,12 @@ mod tests {
         assert_eq!(delivery.routing_key.as_str(), "routing-key");
         Ok(())
     }
+
+    #[test]
+    fn rabbitmq_expose_ports() {
+        assert_eq!(
+            rabbitmq::RabbitMq::default().expose_ports(),
+            [rabbitmq::RABBITMQ_PORT, rabbitmq::RABBITMQ_MANAGEMENT_PORT]
+        );
+    }
 }
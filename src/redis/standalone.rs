@@ -1,4 +1,11 @@
-use testcontainers::{core::WaitFor, Image};
+use std::{borrow::Cow, time::Duration};
+
+use testcontainers::{
+    core::{ContainerState, ExecCommand, WaitFor},
+    Image, TestcontainersError,
+};
+
+use crate::util::retry_until_success;
 
 const NAME: &str = "redis";
 const TAG: &str = "5.0";
@@ -35,10 +42,27 @@ const TAG: &str = "5.0";
 /// [`REDIS_PORT`]: super::REDIS_PORT
 #[derive(Debug, Default, Clone)]
 pub struct Redis {
-    /// (remove if there is another variable)
-    /// Field is included to prevent this struct to be a unit struct.
-    /// This allows extending functionality (and thus further variables) without breaking changes
-    _priv: (),
+    password: Option<String>,
+    auth_ready_check: bool,
+}
+
+impl Redis {
+    /// Requires clients to authenticate with `password` (see [`requirepass`]).
+    ///
+    /// [`requirepass`]: https://redis.io/docs/latest/operate/oss_and_stack/management/security/#authentication
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Additionally waits until an authenticated `PING` returns `PONG` before the container
+    /// is considered ready. Starting the container fails if it doesn't within 600 attempts.
+    ///
+    /// Has no effect if no password is set via [`Redis::with_password`].
+    pub fn with_auth_ready_check(mut self) -> Self {
+        self.auth_ready_check = true;
+        self
+    }
 }
 
 impl Image for Redis {
@@ -53,6 +77,31 @@ impl Image for Redis {
     fn ready_conditions(&self) -> Vec<WaitFor> {
         vec![WaitFor::message_on_stdout("Ready to accept connections")]
     }
+
+    fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
+        let mut cmd = vec!["redis-server"];
+        if let Some(password) = &self.password {
+            cmd.push("--requirepass");
+            cmd.push(password);
+        }
+        cmd
+    }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        let Some(password) = self.password.as_deref().filter(|_| self.auth_ready_check) else {
+            return Ok(vec![]);
+        };
+        // the password is passed as a positional argument to avoid quoting it
+        Ok(vec![retry_until_success(
+            r#"redis-cli -a "$1" ping 2>/dev/null | grep -q PONG"#,
+            [password],
+            600,
+            Duration::from_millis(100),
+        )])
+    }
 }
 
 #[cfg(test)]
@@ -77,4 +126,25 @@ mod tests {
         assert_eq!(42, result);
         Ok(())
     }
+
+    #[test]
+    fn redis_auth_ready_check() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let node = Redis::default()
+            .with_password("secret")
+            .with_auth_ready_check()
+            .start()?;
+        let host_ip = node.get_host()?;
+        let host_port = node.get_host_port_ipv4(6379)?;
+
+        let unauthenticated = redis::Client::open(format!("redis://{host_ip}:{host_port}"))?;
+        let result = redis::cmd("PING").query::<String>(&mut unauthenticated.get_connection()?);
+        assert!(result.is_err());
+
+        let client = redis::Client::open(format!("redis://:secret@{host_ip}:{host_port}"))?;
+        let mut con = client.get_connection()?;
+        let pong: String = redis::cmd("PING").query(&mut con)?;
+        assert_eq!(pong, "PONG");
+        Ok(())
+    }
 }
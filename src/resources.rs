@@ -0,0 +1,17 @@
+use testcontainers::{ContainerRequest, Image, ImageExt};
+
+/// Resource constraints for modules whose containers regularly run out of memory
+/// or file descriptors in constrained CI environments.
+pub trait ResourceLimits: Image + Sized {
+    /// Sets the heap size of the service inside of the container to `bytes`.
+    ///
+    /// This is not a memory cap of the container, which also includes the memory the service
+    /// uses outside of its heap. Modules may raise values the service can't start with;
+    /// see the implementations for details.
+    fn with_heap_size_bytes(self, bytes: u64) -> Self;
+
+    /// Sets the soft and (optionally) hard `nofile` ulimit of the container.
+    fn with_nofile_limit(self, soft: i64, hard: Option<i64>) -> ContainerRequest<Self> {
+        self.with_ulimit("nofile", soft, hard)
+    }
+}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, fmt::Display};
 
 use testcontainers::{
     core::{ContainerPort, WaitFor},
@@ -85,6 +85,36 @@ impl SurrealDb {
             .insert("SURREAL_CAPS_ALLOW_ALL".to_owned(), allow_all.to_string());
         self
     }
+
+    /// Stores the data in an external [`TiKV`] cluster instead of memory,
+    /// with `pd_endpoint` being the `host:port` of its placement driver (PD).
+    ///
+    /// The TiKV cluster is not started by this module: PD and TiKV have to run in their own containers,
+    /// on a network shared with SurrealDB (see [`ImageExt::with_network`]), and have to be ready
+    /// before SurrealDB is started. Several SurrealDB containers pointing to the same PD form
+    /// a cluster of stateless nodes sharing the same data.
+    ///
+    /// [`TiKV`]: https://tikv.org/
+    /// [`ImageExt::with_network`]: testcontainers::ImageExt::with_network
+    pub fn with_tikv(mut self, pd_endpoint: impl Into<String>) -> Self {
+        self.env_vars.insert(
+            "SURREAL_PATH".to_owned(),
+            format!("tikv://{}", pd_endpoint.into()),
+        );
+        self
+    }
+
+    /// Returns the WebSocket URL of the instance, e.g. `ws://127.0.0.1:8000`,
+    /// with `host_port` being the host port [`SURREALDB_PORT`] is mapped to.
+    pub fn ws_url(&self, host: impl Display, host_port: u16) -> String {
+        format!("ws://{host}:{host_port}")
+    }
+
+    /// Returns the HTTP URL of the instance, e.g. `http://127.0.0.1:8000`,
+    /// with `host_port` being the host port [`SURREALDB_PORT`] is mapped to.
+    pub fn http_url(&self, host: impl Display, host_port: u16) -> String {
+        format!("http://{host}:{host_port}")
+    }
 }
 
 impl Default for SurrealDb {
@@ -132,11 +162,14 @@ impl Image for SurrealDb {
 mod tests {
     use serde::{Deserialize, Serialize};
     use surrealdb::{
-        engine::remote::ws::{Client, Ws},
+        engine::{
+            any,
+            remote::ws::{Client, Ws},
+        },
         opt::auth::Root,
         Surreal,
     };
-    use testcontainers::runners::AsyncRunner;
+    use testcontainers::{runners::AsyncRunner, GenericImage, ImageExt};
 
     use super::*;
 
@@ -153,15 +186,33 @@ mod tests {
         marketing: bool,
     }
 
+    #[test]
+    fn surrealdb_urls() {
+        let surrealdb = SurrealDb::default();
+        assert_eq!(surrealdb.ws_url("127.0.0.1", 8000), "ws://127.0.0.1:8000");
+        assert_eq!(
+            surrealdb.http_url("localhost", 18000),
+            "http://localhost:18000"
+        );
+    }
+
     #[tokio::test]
     async fn surrealdb_select() -> Result<(), Box<dyn std::error::Error + 'static>> {
         let _ = pretty_env_logger::try_init();
         let node = SurrealDb::default().start().await?;
+        let host = node.get_host().await?;
         let host_port = node.get_host_port_ipv4(SURREALDB_PORT).await?;
-        let url = format!("127.0.0.1:{host_port}");
 
-        let db: Surreal<Client> = Surreal::init();
-        db.connect::<Ws>(url).await.unwrap();
+        let health = reqwest::get(format!(
+            "{}/health",
+            node.image().http_url(&host, host_port)
+        ))
+        .await?;
+        assert!(health.status().is_success());
+
+        let db = any::connect(node.image().ws_url(&host, host_port))
+            .await
+            .unwrap();
         db.signin(Root {
             username: "root",
             password: "root",
@@ -238,4 +289,83 @@ mod tests {
         assert!(result.marketing);
         Ok(())
     }
+
+    #[tokio::test]
+    #[ignore = "starts a TiKV cluster, which is slow and resource hungry"]
+    async fn surrealdb_tikv() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let network = "surrealdb-tikv";
+
+        let _pd = GenericImage::new("pingcap/pd", "v7.5.1")
+            .with_wait_for(WaitFor::seconds(5))
+            .with_network(network)
+            .with_container_name("surrealdb-tikv-pd")
+            .with_cmd([
+                "--name=pd",
+                "--client-urls=http://0.0.0.0:2379",
+                "--peer-urls=http://0.0.0.0:2380",
+                "--advertise-client-urls=http://surrealdb-tikv-pd:2379",
+                "--advertise-peer-urls=http://surrealdb-tikv-pd:2380",
+            ])
+            .start()
+            .await?;
+        let _tikv = GenericImage::new("pingcap/tikv", "v7.5.1")
+            .with_wait_for(WaitFor::seconds(10))
+            .with_network(network)
+            .with_container_name("surrealdb-tikv-kv")
+            .with_cmd([
+                "--addr=0.0.0.0:20160",
+                "--advertise-addr=surrealdb-tikv-kv:20160",
+                "--pd=surrealdb-tikv-pd:2379",
+            ])
+            .start()
+            .await?;
+
+        // two SurrealDB nodes sharing the same storage
+        let writer = SurrealDb::default()
+            .with_tikv("surrealdb-tikv-pd:2379")
+            .with_network(network)
+            .start()
+            .await?;
+        let reader = SurrealDb::default()
+            .with_tikv("surrealdb-tikv-pd:2379")
+            .with_network(network)
+            .start()
+            .await?;
+
+        let mut clients = vec![];
+        for node in [&writer, &reader] {
+            let url = format!(
+                "127.0.0.1:{}",
+                node.get_host_port_ipv4(SURREALDB_PORT).await?
+            );
+            let db: Surreal<Client> = Surreal::init();
+            db.connect::<Ws>(url).await?;
+            db.signin(Root {
+                username: "root",
+                password: "root",
+            })
+            .await?;
+            db.use_ns("test").use_db("test").await?;
+            clients.push(db);
+        }
+
+        clients[0]
+            .create::<Option<Person>>(("person", "tobie"))
+            .content(Person {
+                title: "Founder & CEO".to_string(),
+                name: Name {
+                    first: "Tobie".to_string(),
+                    last: "Morgan Hitchcock".to_string(),
+                },
+                marketing: true,
+            })
+            .await?;
+
+        let result = clients[1]
+            .select::<Option<Person>>(("person", "tobie"))
+            .await?;
+        assert_eq!(result.unwrap().name.first, "Tobie");
+        Ok(())
+    }
 }
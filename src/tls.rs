@@ -0,0 +1,75 @@
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+#[cfg(feature = "hashicorp_vault")]
+use testcontainers::core::{wait::HttpWaitStrategy, ContainerPort};
+
+/// Names the generated server certificates are always valid for.
+const LOCAL_NAMES: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+/// PEM encoded server certificate and key, signed by a generated root CA.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GeneratedCert {
+    pub(crate) cert: String,
+    pub(crate) key: String,
+    pub(crate) ca: String,
+}
+
+impl GeneratedCert {
+    /// Generate a self-signed root CA with the common name `ca_name`,
+    /// and a server certificate signed by it.
+    ///
+    /// SAN list includes `hostnames` followed by "localhost", "127.0.0.1" and "::1".
+    pub(crate) fn new(ca_name: &str, hostnames: &[&str]) -> Self {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_cert = CertificateParams::new(vec![ca_name.to_string()]).unwrap();
+        ca_cert.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_cert = ca_cert.self_signed(&ca_key).unwrap();
+
+        let mut sans: Vec<String> = hostnames
+            .iter()
+            .filter(|h| !LOCAL_NAMES.contains(h))
+            .map(|h| h.to_string())
+            .collect();
+        sans.extend(LOCAL_NAMES.map(String::from));
+
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(sans)
+            .unwrap()
+            .signed_by(&key, &ca_cert, &ca_key)
+            .unwrap();
+
+        Self {
+            cert: cert.pem(),
+            key: key.serialize_pem(),
+            ca: ca_cert.pem(),
+        }
+    }
+}
+
+/// Builds the client of HTTPS readiness checks.
+///
+/// The server certificate is verified against `ca` if it's known, e.g. generated by [`GeneratedCert`],
+/// and accepted as is otherwise, since the issuer of a provided certificate is unknown.
+#[cfg(any(feature = "hashicorp_vault", feature = "meilisearch"))]
+pub(crate) fn https_client(ca: Option<&str>) -> reqwest::Client {
+    match ca {
+        Some(ca) => reqwest::Client::builder().add_root_certificate(
+            reqwest::Certificate::from_pem(ca.as_bytes()).expect("CA certificate is valid PEM"),
+        ),
+        None => reqwest::Client::builder().danger_accept_invalid_certs(true),
+    }
+    .build()
+    .expect("failed to build the HTTPS client for the readiness check")
+}
+
+/// Waits for a response of `path` on `port` over HTTPS, see [`https_client`].
+#[cfg(feature = "hashicorp_vault")]
+pub(crate) fn https_wait(
+    path: impl Into<String>,
+    port: ContainerPort,
+    ca: Option<&str>,
+) -> HttpWaitStrategy {
+    HttpWaitStrategy::new(path)
+        .with_port(port)
+        .with_tls()
+        .with_client(https_client(ca))
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+#[cfg(any(feature = "meilisearch", feature = "postgres", feature = "valkey"))]
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
+#[cfg(feature = "meilisearch")]
+use serde_json::Value;
+#[cfg(feature = "meilisearch")]
+use testcontainers::core::{wait::HttpWaitStrategy, ContainerPort};
+use testcontainers::core::{CmdWaitFor, ExecCommand};
+
+/// Characters percent-encoded in the user and password of connection URLs,
+/// all but the unreserved ones.
+#[cfg(any(feature = "meilisearch", feature = "postgres", feature = "valkey"))]
+pub(crate) const USERINFO: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Characters percent-encoded in a path segment of connection URLs, e.g. the database name,
+/// which are the same as in the user and password.
+#[cfg(any(feature = "meilisearch", feature = "postgres"))]
+pub(crate) const PATH_SEGMENT: &AsciiSet = USERINFO;
+
+/// Command running the shell `probe` every `interval` until it succeeds,
+/// failing after `attempts` unsuccessful runs.
+///
+/// `args` are passed to the probe as the positional parameters `$1`, `$2`, ...,
+/// so values like passwords don't need to be quoted.
+pub(crate) fn retry_until_success<I, S>(
+    probe: &str,
+    args: I,
+    attempts: u32,
+    interval: Duration,
+) -> ExecCommand
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let script = format!(
+        r#"i=0; until {probe}; do i=$((i+1)); [ "$i" -ge {attempts} ] && exit 1; sleep {}; done"#,
+        interval.as_secs_f64()
+    );
+    let cmd = ["sh".to_owned(), "-c".to_owned(), script, "sh".to_owned()]
+        .into_iter()
+        .chain(args.into_iter().map(Into::into));
+    ExecCommand::new(cmd).with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+}
+
+/// Waits for a successful response of `path` on `port`, whose body is JSON equal to `expected_json`.
+///
+/// Bodies are compared as parsed JSON values, so formatting and the order of object keys don't matter.
+/// If `expected_json` isn't valid JSON, no response matches.
+#[cfg(feature = "meilisearch")]
+pub(crate) fn http_ready(
+    path: impl Into<String>,
+    port: ContainerPort,
+    expected_json: &str,
+) -> HttpWaitStrategy {
+    let expected = serde_json::from_str::<Value>(expected_json).ok();
+    HttpWaitStrategy::new(path)
+        .with_port(port)
+        .with_response_matcher_async(move |response| {
+            let expected = expected.clone();
+            async move {
+                let Some(expected) = expected else {
+                    return false;
+                };
+                if !response.status().is_success() {
+                    return false;
+                }
+                match response.bytes().await {
+                    Ok(body) => json_matches(&body, &expected),
+                    Err(_) => false,
+                }
+            }
+        })
+}
+
+/// Whether `body` is JSON equal to `expected`.
+#[cfg(feature = "meilisearch")]
+fn json_matches(body: &[u8], expected: &Value) -> bool {
+    serde_json::from_slice::<Value>(body).is_ok_and(|body| &body == expected)
+}
+
+#[cfg(all(test, feature = "meilisearch"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn json_matches_ignores_formatting() {
+        let expected = json!({ "status": "available", "code": 200 });
+        assert!(json_matches(
+            br#"{"status":"available","code":200}"#,
+            &expected
+        ));
+        assert!(json_matches(
+            b"{\n  \"code\": 200,\n  \"status\" : \"available\"\n}\n",
+            &expected
+        ));
+    }
+
+    #[test]
+    fn json_matches_rejects_other_bodies() {
+        let expected = json!({ "status": "available" });
+        assert!(!json_matches(br#"{"status":"unavailable"}"#, &expected));
+        assert!(!json_matches(
+            br#"{"status":"available","extra":true}"#,
+            &expected
+        ));
+        assert!(!json_matches(b"available", &expected));
+    }
+
+    #[test]
+    fn http_ready_does_not_panic_on_invalid_json() {
+        let _strategy = http_ready("/health", ContainerPort::Tcp(8080), "{ invalid");
+    }
+}
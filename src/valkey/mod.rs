@@ -1,8 +1,15 @@
+use std::{borrow::Cow, fmt::Display};
+
+use percent_encoding::utf8_percent_encode;
+#[cfg(feature = "blocking")]
+use testcontainers::Container;
 use testcontainers::{
-    core::{ContainerPort, WaitFor},
-    Image,
+    core::{CmdWaitFor, ContainerPort, ContainerState, ExecCommand, WaitFor},
+    ContainerAsync, Image, TestcontainersError,
 };
 
+use crate::util::USERINFO;
+
 const NAME: &str = "valkey/valkey";
 const TAG: &str = "8.0.1-alpine";
 
@@ -19,22 +26,23 @@ pub const VALKEY_PORT: ContainerPort = ContainerPort::Tcp(6379);
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
 /// use redis::Commands;
 /// use testcontainers_modules::{
 ///     testcontainers::runners::SyncRunner,
-///     valkey::{Valkey, VALKEY_PORT},
+///     valkey::{self, Valkey},
 /// };
 ///
 /// let valkey_instance = Valkey::default().start().unwrap();
-/// let host_ip = valkey_instance.get_host().unwrap();
-/// let host_port = valkey_instance.get_host_port_ipv4(VALKEY_PORT).unwrap();
 ///
-/// let url = format!("redis://{host_ip}:{host_port}");
+/// let url = valkey::connection_url_blocking(&valkey_instance).unwrap();
 /// let client = redis::Client::open(url.as_ref()).unwrap();
 /// let mut con = client.get_connection().unwrap();
 ///
 /// con.set::<_, _, ()>("my_key", 42).unwrap();
 /// let result: i64 = con.get("my_key").unwrap();
+/// # }
 /// ```
 ///
 /// [`Valkey`]: https://valkey.io/
@@ -42,10 +50,71 @@ pub const VALKEY_PORT: ContainerPort = ContainerPort::Tcp(6379);
 /// [`VALKEY_PORT`]: super::VALKEY_PORT
 #[derive(Debug, Default, Clone)]
 pub struct Valkey {
-    /// (remove if there is another variable)
-    /// Field is included to prevent this struct to be a unit struct.
-    /// This allows extending functionality (and thus further variables) without breaking changes
-    _priv: (),
+    io_threads: Option<u32>,
+    password: Option<String>,
+    flush_on_start: bool,
+}
+
+impl Valkey {
+    /// Sets the number of I/O threads (`--io-threads`) Valkey uses to handle client connections.
+    ///
+    /// See the [`valkey.conf` documentation](https://valkey.io/topics/valkey.conf/) for details.
+    pub fn with_io_threads(mut self, io_threads: u32) -> Self {
+        self.io_threads = Some(io_threads);
+        self
+    }
+
+    /// Requires clients to authenticate with `password` (see [`requirepass`]).
+    ///
+    /// [`requirepass`]: https://valkey.io/topics/security/#authentication
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Runs `FLUSHALL` after start, so the keyspace is empty even if data was loaded on startup,
+    /// e.g. from a dump in a mounted `/data` directory reused across tests.
+    pub fn with_flush_on_start(mut self) -> Self {
+        self.flush_on_start = true;
+        self
+    }
+
+    /// Returns a redis-compatible URL to connect to the server, e.g. `redis://localhost:32768`.
+    ///
+    /// If a password is set with [`Valkey::with_password`], it is included percent-encoded:
+    /// `redis://:<password>@localhost:32768`.
+    ///
+    /// See [`connection_url`] and [`connection_url_blocking`] to get the URL of a running container.
+    pub fn connection_url(&self, host: impl Display, host_port: u16) -> String {
+        match &self.password {
+            Some(password) => format!(
+                "redis://:{}@{host}:{host_port}",
+                utf8_percent_encode(password, USERINFO)
+            ),
+            None => format!("redis://{host}:{host_port}"),
+        }
+    }
+}
+
+/// Returns the [`Valkey::connection_url`] of a container started with the `AsyncRunner`,
+/// using the host port mapped to [`VALKEY_PORT`].
+pub async fn connection_url(
+    container: &ContainerAsync<Valkey>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host().await?;
+    let port = container.get_host_port_ipv4(VALKEY_PORT).await?;
+    Ok(container.image().connection_url(host, port))
+}
+
+/// Returns the [`Valkey::connection_url`] of a container started with the `SyncRunner`,
+/// using the host port mapped to [`VALKEY_PORT`].
+#[cfg(feature = "blocking")]
+pub fn connection_url_blocking(
+    container: &Container<Valkey>,
+) -> Result<String, TestcontainersError> {
+    let host = container.get_host()?;
+    let port = container.get_host_port_ipv4(VALKEY_PORT)?;
+    Ok(container.image().connection_url(host, port))
 }
 
 impl Image for Valkey {
@@ -60,13 +129,114 @@ impl Image for Valkey {
     fn ready_conditions(&self) -> Vec<WaitFor> {
         vec![WaitFor::message_on_stdout("Ready to accept connections")]
     }
+
+    fn cmd(&self) -> impl IntoIterator<Item = impl Into<Cow<'_, str>>> {
+        let mut cmd = vec!["valkey-server".to_string()];
+        if let Some(io_threads) = self.io_threads {
+            cmd.push("--io-threads".to_string());
+            cmd.push(io_threads.to_string());
+        }
+        if let Some(password) = &self.password {
+            cmd.push("--requirepass".to_string());
+            cmd.push(password.clone());
+        }
+        cmd
+    }
+
+    fn exec_after_start(
+        &self,
+        _cs: ContainerState,
+    ) -> Result<Vec<ExecCommand>, TestcontainersError> {
+        if !self.flush_on_start {
+            return Ok(vec![]);
+        }
+        let mut cmd = vec!["valkey-cli".to_string()];
+        if let Some(password) = &self.password {
+            cmd.extend([
+                "-a".to_string(),
+                password.clone(),
+                "--no-auth-warning".to_string(),
+            ]);
+        }
+        cmd.push("FLUSHALL".to_string());
+        Ok(vec![
+            ExecCommand::new(cmd).with_cmd_ready_condition(CmdWaitFor::exit_code(0))
+        ])
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use redis::Commands;
+    use testcontainers::{core::Mount, ImageExt};
+
+    use crate::{
+        testcontainers::runners::SyncRunner,
+        valkey::{connection_url, Valkey, VALKEY_PORT},
+    };
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn valkey_connection_url() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        use crate::valkey::connection_url_blocking;
+
+        let node = Valkey::default().start()?;
+        let url = connection_url_blocking(&node)?;
+        assert!(url.starts_with("redis://"));
 
-    use crate::{testcontainers::runners::SyncRunner, valkey::Valkey};
+        let client = redis::Client::open(url.as_ref())?;
+        let mut con = client.get_connection()?;
+        let pong: String = redis::cmd("PING").query(&mut con)?;
+        assert_eq!(pong, "PONG");
+        Ok(())
+    }
+
+    #[test]
+    fn valkey_connection_url_encodes_password() {
+        let valkey = Valkey::default().with_password("p@ss:w/rd%");
+        assert_eq!(
+            valkey.connection_url("localhost", 6379),
+            "redis://:p%40ss%3Aw%2Frd%25@localhost:6379"
+        );
+    }
+
+    #[test]
+    fn valkey_password() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = Valkey::default().with_password("secret").start()?;
+        let host_ip = node.get_host()?;
+        let host_port = node.get_host_port_ipv4(VALKEY_PORT)?;
+        let url = node.image().connection_url(&host_ip, host_port);
+        assert!(url.starts_with("redis://:secret@"));
+
+        let unauthenticated = redis::Client::open(format!("redis://{host_ip}:{host_port}"))?;
+        let result = unauthenticated
+            .get_connection()?
+            .get::<_, Option<i64>>("my_key");
+        assert!(result.is_err());
+
+        let client = redis::Client::open(url.as_ref())?;
+        let mut con = client.get_connection()?;
+        con.set::<_, _, ()>("my_key", 42)?;
+        let result: i64 = con.get("my_key")?;
+        assert_eq!(42, result);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn valkey_connection_url_async() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let node = testcontainers::runners::AsyncRunner::start(Valkey::default()).await?;
+        let url = connection_url(&node).await?;
+
+        // the sync client can't be used on the async runtime directly
+        let pong = tokio::task::spawn_blocking(move || {
+            let client = redis::Client::open(url.as_ref())?;
+            let mut con = client.get_connection()?;
+            redis::cmd("PING").query::<String>(&mut con)
+        })
+        .await??;
+        assert_eq!(pong, "PONG");
+        Ok(())
+    }
 
     #[test]
     fn valkey_fetch_an_integer() -> Result<(), Box<dyn std::error::Error + 'static>> {
@@ -84,4 +254,79 @@ mod tests {
         assert_eq!(42, result);
         Ok(())
     }
+
+    #[test]
+    fn valkey_io_threads() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        let _ = pretty_env_logger::try_init();
+        let node = Valkey::default().with_io_threads(4).start()?;
+        let host_ip = node.get_host()?;
+        let host_port = node.get_host_port_ipv4(6379)?;
+        let url = format!("redis://{host_ip}:{host_port}");
+
+        let client = redis::Client::open(url.as_ref()).unwrap();
+        let mut con = client.get_connection().unwrap();
+
+        let config: Vec<String> = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("io-threads")
+            .query(&mut con)
+            .unwrap();
+        assert_eq!(config, vec!["io-threads", "4"]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn valkey_flush_on_start() -> Result<(), Box<dyn std::error::Error + 'static>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _ = pretty_env_logger::try_init();
+        let data_dir = std::env::temp_dir().join(format!("valkey-data-{}", std::process::id()));
+        std::fs::create_dir_all(&data_dir)?;
+        // the server doesn't run as root
+        std::fs::set_permissions(&data_dir, std::fs::Permissions::from_mode(0o777))?;
+        let data_mount = || Mount::bind_mount(data_dir.to_string_lossy(), "/data");
+
+        // seed the dump in the mounted directory
+        let node = Valkey::default().with_mount(data_mount()).start()?;
+        let url = node
+            .image()
+            .connection_url(node.get_host()?, node.get_host_port_ipv4(VALKEY_PORT)?);
+        let client = redis::Client::open(url.as_ref())?;
+        let mut con = client.get_connection()?;
+        con.set::<_, _, ()>("my_key", 42)?;
+        redis::cmd("SAVE").query::<()>(&mut con)?;
+        node.rm()?;
+
+        // without the flush, the keys of the dump are loaded on startup
+        let node = Valkey::default().with_mount(data_mount()).start()?;
+        let url = node
+            .image()
+            .connection_url(node.get_host()?, node.get_host_port_ipv4(VALKEY_PORT)?);
+        let client = redis::Client::open(url.as_ref())?;
+        let mut con = client.get_connection()?;
+        let keys: i64 = redis::cmd("DBSIZE").query(&mut con)?;
+        assert_eq!(keys, 1);
+        let value: i64 = con.get("my_key")?;
+        assert_eq!(value, 42);
+        node.rm()?;
+
+        // the dump is loaded on startup, and flushed afterwards
+        let node = Valkey::default()
+            .with_flush_on_start()
+            .with_mount(data_mount())
+            .start()?;
+        let url = node
+            .image()
+            .connection_url(node.get_host()?, node.get_host_port_ipv4(VALKEY_PORT)?);
+        let client = redis::Client::open(url.as_ref())?;
+        let mut con = client.get_connection()?;
+        let keys: i64 = redis::cmd("DBSIZE").query(&mut con)?;
+        assert_eq!(keys, 0);
+
+        node.rm()?;
+        // the dump is owned by the user of the container, so this may fail
+        let _ = std::fs::remove_dir_all(&data_dir);
+        Ok(())
+    }
 }